@@ -0,0 +1,43 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+use your_time_is_currency::messages::MessageData;
+
+/// Counts allocated bytes, so decoder cannot allocate much more than frame it reads.
+struct CountingAlloc;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATED.fetch_add(new_size, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOC: CountingAlloc = CountingAlloc;
+
+// Any frame must either be refused or decode to message that encodes back to same bytes,
+// allocating memory proportional to its size on the way.
+fuzz_target!(|data: &[u8]| {
+    let before = ALLOCATED.load(Ordering::Relaxed);
+    let result = MessageData::from_frame(data);
+    let allocated = ALLOCATED.load(Ordering::Relaxed) - before;
+    assert!(allocated <= data.len() * 32 + 1024);
+    if let Ok(message) = result {
+        assert_eq!(message.to_frame(), data);
+    }
+});
@@ -0,0 +1,9 @@
+use oxygengine::prelude::*;
+
+/// Radius of circle around entity position that `CollisionSystem` tests.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Collider(pub Scalar);
+
+impl Component for Collider {
+    type Storage = VecStorage<Self>;
+}
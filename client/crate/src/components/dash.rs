@@ -0,0 +1,109 @@
+use crate::{
+    consts::{DASH_COOLDOWN, DASH_COST, DASH_DURATION, DASH_MULTIPLIER, MIN_DASH_TIME},
+    messages::PLAYER_FLAG_DASH,
+};
+use oxygengine::prelude::*;
+
+/// Short burst of speed paid with time, followed by cooldown.
+#[derive(Debug, Clone)]
+pub struct Dash {
+    /// Time spent on every dash.
+    pub cost: Scalar,
+    pub duration: f64,
+    /// Applied to `Speed` while dashing.
+    pub multiplier: Scalar,
+    pub cooldown: f64,
+    /// Player cannot dash if it would leave less time than this.
+    pub min_time: Scalar,
+    /// Time left of current dash.
+    active: f64,
+    /// Time left until next dash can start.
+    recharge: f64,
+}
+
+impl Default for Dash {
+    fn default() -> Self {
+        Self::new(
+            DASH_COST,
+            DASH_DURATION,
+            DASH_MULTIPLIER,
+            DASH_COOLDOWN,
+            MIN_DASH_TIME,
+        )
+    }
+}
+
+impl Dash {
+    pub fn new(
+        cost: Scalar,
+        duration: f64,
+        multiplier: Scalar,
+        cooldown: f64,
+        min_time: Scalar,
+    ) -> Self {
+        Self {
+            cost,
+            duration,
+            multiplier,
+            cooldown,
+            min_time,
+            active: 0.0,
+            recharge: 0.0,
+        }
+    }
+
+    pub fn update(&mut self, delta_time: f64) {
+        self.active = (self.active - delta_time).max(0.0);
+        self.recharge = (self.recharge - delta_time).max(0.0);
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active > 0.0
+    }
+
+    /// Tells if player with given time left may dash now.
+    pub fn can_start(&self, time: Scalar) -> bool {
+        !self.is_active() && self.recharge <= 0.0 && time - self.cost >= self.min_time
+    }
+
+    /// Starts dash and takes its cost from `time`; returns false if dash is not allowed.
+    pub fn start(&mut self, time: &mut Scalar) -> bool {
+        if !self.can_start(*time) {
+            return false;
+        }
+        *time -= self.cost;
+        self.active = self.duration;
+        self.recharge = self.cooldown;
+        true
+    }
+
+    /// Shows dash reported by remote player state, without paying for it.
+    pub fn set_active(&mut self, active: bool) {
+        if !active {
+            self.active = 0.0;
+        } else if !self.is_active() {
+            self.active = self.duration;
+        }
+    }
+
+    pub fn speed_multiplier(&self) -> Scalar {
+        if self.is_active() {
+            self.multiplier
+        } else {
+            1.0
+        }
+    }
+
+    /// `PLAYER_FLAG_*` bits describing this dash.
+    pub fn flags(&self) -> u8 {
+        if self.is_active() {
+            PLAYER_FLAG_DASH
+        } else {
+            0
+        }
+    }
+}
+
+impl Component for Dash {
+    type Storage = VecStorage<Self>;
+}
@@ -7,6 +7,10 @@ impl EnemyController {
     pub fn new(id: u32) -> Self {
         Self(id)
     }
+
+    pub fn id(&self) -> u32 {
+        self.0
+    }
 }
 
 impl Component for EnemyController {
@@ -1,9 +1,16 @@
+pub mod collider;
+pub mod dash;
 pub mod enemy_controller;
 pub mod follow;
 pub mod owned_by;
+pub mod pickup;
 pub mod player_controller;
+pub mod send_policy;
+pub mod snapshot_buffer;
 pub mod speed;
 pub mod time;
+pub mod time_decay;
+pub mod upgrades;
 pub mod velocity;
 
 use oxygengine::prelude::*;
@@ -0,0 +1,56 @@
+use crate::consts::{PICKUP_PULSE_RATE, PICKUP_PULSE_SCALE, PICKUP_REQUEST_DELAY};
+use oxygengine::prelude::*;
+
+/// Time orb that player collects by touching it.
+#[derive(Debug, Clone)]
+pub struct Pickup {
+    pub id: u32,
+    /// Time given to player that collects it.
+    pub time: Scalar,
+    /// Time since spawn, drives pulse animation.
+    age: f64,
+    /// Time left until collection can be requested again.
+    cooldown: f64,
+    requested: bool,
+}
+
+impl Pickup {
+    pub fn new(id: u32, time: Scalar) -> Self {
+        Self {
+            id,
+            time,
+            age: 0.0,
+            cooldown: 0.0,
+            requested: false,
+        }
+    }
+
+    pub fn update(&mut self, delta_time: f64) {
+        self.age += delta_time;
+        self.cooldown -= delta_time;
+    }
+
+    /// Current scale of pickup sprite.
+    pub fn pulse(&self) -> Scalar {
+        let phase = self.age * PICKUP_PULSE_RATE * std::f64::consts::PI * 2.0;
+        1.0 + phase.sin() as Scalar * PICKUP_PULSE_SCALE
+    }
+
+    /// Called while player touches pickup, collection is requested at most every
+    /// `PICKUP_REQUEST_DELAY` seconds in case server did not agree.
+    pub fn touch(&mut self) {
+        if self.cooldown <= 0.0 {
+            self.cooldown = PICKUP_REQUEST_DELAY;
+            self.requested = true;
+        }
+    }
+
+    /// Returns true once for every accepted touch.
+    pub fn take_request(&mut self) -> bool {
+        std::mem::replace(&mut self.requested, false)
+    }
+}
+
+impl Component for Pickup {
+    type Storage = VecStorage<Self>;
+}
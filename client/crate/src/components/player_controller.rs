@@ -1,16 +1,105 @@
+use crate::{
+    consts::{MAX_INPUT_DELTA, MAX_INPUT_HISTORY},
+    messages::{MsgPlayerCorrection, MsgPlayerInput},
+};
 use oxygengine::prelude::*;
+use std::collections::VecDeque;
 
-#[derive(Debug, Default, Copy, Clone)]
-pub struct PlayerController(u32);
+#[derive(Debug, Clone)]
+pub struct PredictedInput {
+    pub input: MsgPlayerInput,
+    /// Predicted position after input was applied.
+    pub position: Vec2,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct PlayerController {
+    id: u32,
+    sequence: u32,
+    /// Inputs applied locally but not yet acknowledged by server.
+    history: VecDeque<PredictedInput>,
+    /// Inputs not yet sent to server.
+    outbox: Vec<MsgPlayerInput>,
+    correction: Option<MsgPlayerCorrection>,
+}
 
 impl PlayerController {
     pub fn new(id: u32) -> Self {
-        Self(id)
+        Self {
+            id,
+            ..Default::default()
+        }
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Records input applied locally. Unsent input with same direction and flags is extended
+    /// by it instead, so server gets one input per send rather than one per frame.
+    pub fn record(&mut self, direction: Vec2, delta_time: Scalar, flags: u8, position: Vec2) {
+        self.sequence += 1;
+        let input = MsgPlayerInput {
+            sequence: self.sequence,
+            direction,
+            delta_time,
+            flags,
+        };
+        match self.outbox.last_mut() {
+            Some(last)
+                if last.direction == direction
+                    && last.flags == flags
+                    && last.delta_time + delta_time <= MAX_INPUT_DELTA =>
+            {
+                last.sequence = input.sequence;
+                last.delta_time += delta_time;
+            }
+            _ => self.outbox.push(input.clone()),
+        }
+        if self.history.len() >= MAX_INPUT_HISTORY {
+            self.history.pop_front();
+        }
+        self.history.push_back(PredictedInput { input, position });
+    }
+
+    pub fn drain_outbox(&mut self) -> Vec<MsgPlayerInput> {
+        std::mem::take(&mut self.outbox)
     }
 
-    // pub fn id(self) -> u32 {
-    //     self.0
-    // }
+    pub fn correct(&mut self, correction: MsgPlayerCorrection) {
+        let newer = self
+            .correction
+            .as_ref()
+            .map(|c| correction.sequence > c.sequence)
+            .unwrap_or(true);
+        if newer {
+            self.correction = Some(correction);
+        }
+    }
+
+    pub fn take_correction(&mut self) -> Option<MsgPlayerCorrection> {
+        self.correction.take()
+    }
+
+    /// Forgets inputs up to given sequence and returns prediction made for that one.
+    pub fn acknowledge(&mut self, sequence: u32) -> Option<PredictedInput> {
+        let mut result = None;
+        while let Some(predicted) = self.history.front() {
+            if predicted.input.sequence > sequence {
+                break;
+            }
+            result = self.history.pop_front();
+        }
+        result
+    }
+
+    pub fn pending_count(&self) -> usize {
+        self.history.len()
+    }
+
+    pub fn pending_mut(&mut self) -> impl Iterator<Item = &mut PredictedInput> {
+        self.history.iter_mut()
+    }
 }
 
 impl Component for PlayerController {
@@ -0,0 +1,136 @@
+use oxygengine::prelude::*;
+
+/// What send policy looks at each frame.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct SendSample {
+    pub delta_time: f64,
+    pub velocity: Vec2,
+}
+
+/// Decides when player state is sent to server.
+pub trait SendPolicy: Send + Sync {
+    /// Called every frame, returns true if state should be sent now.
+    fn update(&mut self, sample: &SendSample) -> bool;
+
+    /// Called after state of given size (in bytes) was sent.
+    fn sent(&mut self, _size: usize) {}
+
+    /// Makes next update send state, e.g. after reconnecting.
+    fn reset(&mut self);
+}
+
+/// Sends state every `delay` seconds no matter what.
+#[derive(Debug, Clone)]
+pub struct FixedSendPolicy {
+    pub delay: f64,
+    timer: f64,
+}
+
+impl FixedSendPolicy {
+    pub fn new(delay: f64) -> Self {
+        Self { delay, timer: 0.0 }
+    }
+}
+
+impl SendPolicy for FixedSendPolicy {
+    fn update(&mut self, sample: &SendSample) -> bool {
+        self.timer -= sample.delta_time;
+        if self.timer <= 0.0 {
+            self.timer = self.delay;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn reset(&mut self) {
+        self.timer = 0.0;
+    }
+}
+
+/// Sends state right away when movement changes, every `move_delay` seconds while moving
+/// and every `idle_delay` seconds while standing still, never exceeding `bandwidth`.
+#[derive(Debug, Clone)]
+pub struct AdaptiveSendPolicy {
+    /// Shortest time between two states, even if movement keeps changing.
+    pub min_delay: f64,
+    pub move_delay: f64,
+    pub idle_delay: f64,
+    /// Bytes per second that states may take.
+    pub bandwidth: f64,
+    elapsed: f64,
+    /// Bytes that can be sent now, refills up to one second of bandwidth.
+    budget: f64,
+    last_size: usize,
+    /// Velocity that was last sent.
+    sent_velocity: Option<Vec2>,
+    /// Velocity that will be sent if update said so.
+    velocity: Vec2,
+}
+
+impl AdaptiveSendPolicy {
+    pub fn new(min_delay: f64, move_delay: f64, idle_delay: f64, bandwidth: f64) -> Self {
+        Self {
+            min_delay,
+            move_delay,
+            idle_delay,
+            bandwidth,
+            elapsed: 0.0,
+            budget: bandwidth,
+            last_size: 0,
+            sent_velocity: None,
+            velocity: Vec2::zero(),
+        }
+    }
+
+    fn movement_changed(&self) -> bool {
+        match self.sent_velocity {
+            Some(velocity) => (velocity - self.velocity).sqr_magnitude() > 1.0e-6,
+            None => true,
+        }
+    }
+}
+
+impl SendPolicy for AdaptiveSendPolicy {
+    fn update(&mut self, sample: &SendSample) -> bool {
+        self.elapsed += sample.delta_time;
+        self.budget = (self.budget + self.bandwidth * sample.delta_time).min(self.bandwidth);
+        self.velocity = sample.velocity;
+        let delay = if self.movement_changed() {
+            self.min_delay
+        } else if self.velocity.sqr_magnitude() > 0.0 {
+            self.move_delay
+        } else {
+            self.idle_delay
+        };
+        self.elapsed >= delay && self.budget >= self.last_size as f64
+    }
+
+    fn sent(&mut self, size: usize) {
+        self.elapsed = 0.0;
+        self.budget -= size as f64;
+        self.last_size = size;
+        self.sent_velocity = Some(self.velocity);
+    }
+
+    fn reset(&mut self) {
+        self.sent_velocity = None;
+        self.elapsed = self.min_delay;
+    }
+}
+
+/// Send policy used for owner's player state.
+pub struct StateSendPolicy(pub Box<dyn SendPolicy>);
+
+impl StateSendPolicy {
+    pub fn new<P>(policy: P) -> Self
+    where
+        P: SendPolicy + 'static,
+    {
+        Self(Box::new(policy))
+    }
+}
+
+impl Component for StateSendPolicy {
+    type Storage = VecStorage<Self>;
+}
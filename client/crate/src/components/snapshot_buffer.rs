@@ -0,0 +1,73 @@
+use oxygengine::prelude::*;
+use std::collections::VecDeque;
+
+#[derive(Debug, Copy, Clone)]
+pub struct Snapshot {
+    /// Server time of this state.
+    pub time: f64,
+    pub position: Vec2,
+    pub velocity: Vec2,
+}
+
+/// Keeps recent server states of remote entity so it can be rendered slightly in the past,
+/// between two known states instead of snapping to each of them.
+#[derive(Debug, Clone)]
+pub struct SnapshotBuffer {
+    /// How far in the past (in seconds) entity is rendered.
+    pub delay: f64,
+    /// Longest time (in seconds) entity is extrapolated past the latest snapshot.
+    pub max_extrapolation: f64,
+    snapshots: VecDeque<Snapshot>,
+}
+
+impl SnapshotBuffer {
+    pub fn new(delay: f64, max_extrapolation: f64) -> Self {
+        Self {
+            delay,
+            max_extrapolation,
+            snapshots: Default::default(),
+        }
+    }
+
+    pub fn push(&mut self, time: f64, position: Vec2, velocity: Vec2) {
+        // states can arrive out of order so keep them sorted by time.
+        let index = self
+            .snapshots
+            .iter()
+            .rposition(|snapshot| snapshot.time <= time)
+            .map(|index| index + 1)
+            .unwrap_or(0);
+        self.snapshots.insert(
+            index,
+            Snapshot {
+                time,
+                position,
+                velocity,
+            },
+        );
+    }
+
+    /// Drops snapshots that will never be used again for given render time.
+    pub fn prune(&mut self, render_time: f64) {
+        while self.snapshots.len() > 1 && self.snapshots[1].time <= render_time {
+            self.snapshots.pop_front();
+        }
+    }
+
+    pub fn sample(&self, render_time: f64) -> Option<Vec2> {
+        let first = self.snapshots.front()?;
+        if render_time <= first.time {
+            return Some(first.position);
+        }
+        if let Some(next) = self.snapshots.get(1) {
+            let factor = ((render_time - first.time) / (next.time - first.time)) as Scalar;
+            return Some(first.position + (next.position - first.position) * factor);
+        }
+        let extrapolation = (render_time - first.time).min(self.max_extrapolation) as Scalar;
+        Some(first.position + first.velocity * extrapolation)
+    }
+}
+
+impl Component for SnapshotBuffer {
+    type Storage = VecStorage<Self>;
+}
@@ -0,0 +1,67 @@
+use oxygengine::prelude::*;
+
+/// How fast `Time` runs out, in seconds lost per second of play.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum DecayCurve {
+    /// Always given rate.
+    Constant(Scalar),
+    /// `1 + factor * time left`, so rich players bleed faster.
+    Proportional(Scalar),
+    /// `1 + factor * seconds since spawn`, so decay speeds up as match goes on.
+    Accelerating(Scalar),
+    /// Given rate while moving and 1 while standing still.
+    Moving(Scalar),
+}
+
+impl Default for DecayCurve {
+    fn default() -> Self {
+        DecayCurve::Constant(1.0)
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct TimeDecay {
+    pub curve: DecayCurve,
+    /// Seconds since spawn, used by accelerating curve.
+    elapsed: f64,
+}
+
+impl TimeDecay {
+    pub fn new(curve: DecayCurve) -> Self {
+        Self {
+            curve,
+            elapsed: 0.0,
+        }
+    }
+
+    pub fn elapsed(&self) -> f64 {
+        self.elapsed
+    }
+
+    /// Current decay rate for given time left.
+    pub fn rate(&self, time: Scalar, moving: bool) -> Scalar {
+        match self.curve {
+            DecayCurve::Constant(rate) => rate,
+            DecayCurve::Proportional(factor) => 1.0 + factor * time.max(0.0),
+            DecayCurve::Accelerating(factor) => 1.0 + factor * self.elapsed as Scalar,
+            DecayCurve::Moving(rate) => {
+                if moving {
+                    rate
+                } else {
+                    1.0
+                }
+            }
+        }
+    }
+
+    /// Moves decay forward and returns how much time is lost in this step.
+    pub fn update(&mut self, delta_time: f64, time: Scalar, moving: bool) -> Scalar {
+        let lost = self.rate(time, moving) * delta_time as Scalar;
+        self.elapsed += delta_time;
+        lost
+    }
+}
+
+impl Component for TimeDecay {
+    type Storage = VecStorage<Self>;
+}
@@ -0,0 +1,76 @@
+use crate::{
+    consts::{
+        MAX_UPGRADE_LEVEL, UPGRADE_BASE_COST, UPGRADE_DECAY_STEP, UPGRADE_PICKUP_RADIUS_STEP,
+        UPGRADE_SPEED_STEP,
+    },
+    messages::UpgradeKind,
+};
+use oxygengine::prelude::*;
+
+/// Upgrade levels bought with time, kept until player dies.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct Upgrades {
+    speed: u8,
+    pickup_radius: u8,
+    decay_rate: u8,
+}
+
+impl Upgrades {
+    /// Time price of given upgrade level.
+    pub fn cost(level: u8) -> Scalar {
+        UPGRADE_BASE_COST * Scalar::from(level)
+    }
+
+    pub fn level(&self, kind: UpgradeKind) -> u8 {
+        match kind {
+            UpgradeKind::Speed => self.speed,
+            UpgradeKind::PickupRadius => self.pickup_radius,
+            UpgradeKind::DecayRate => self.decay_rate,
+        }
+    }
+
+    pub fn set_level(&mut self, kind: UpgradeKind, level: u8) {
+        let level = level.min(MAX_UPGRADE_LEVEL);
+        match kind {
+            UpgradeKind::Speed => self.speed = level,
+            UpgradeKind::PickupRadius => self.pickup_radius = level,
+            UpgradeKind::DecayRate => self.decay_rate = level,
+        }
+    }
+
+    /// Level that can be bought next, `None` if upgrade is maxed out.
+    pub fn next_level(&self, kind: UpgradeKind) -> Option<u8> {
+        let level = self.level(kind);
+        if level < MAX_UPGRADE_LEVEL {
+            Some(level + 1)
+        } else {
+            None
+        }
+    }
+
+    /// Tells if player with given time left can buy next level (and survive it).
+    pub fn can_buy(&self, kind: UpgradeKind, time: Scalar) -> bool {
+        self.next_level(kind)
+            .map(|level| time > Self::cost(level))
+            .unwrap_or(false)
+    }
+
+    /// Applied to `Speed`.
+    pub fn speed_multiplier(&self) -> Scalar {
+        1.0 + UPGRADE_SPEED_STEP * Scalar::from(self.speed)
+    }
+
+    /// Added to player collider when touching pickups.
+    pub fn pickup_radius_bonus(&self) -> Scalar {
+        UPGRADE_PICKUP_RADIUS_STEP * Scalar::from(self.pickup_radius)
+    }
+
+    /// Applied to time decay.
+    pub fn decay_multiplier(&self) -> Scalar {
+        1.0 - UPGRADE_DECAY_STEP * Scalar::from(self.decay_rate)
+    }
+}
+
+impl Component for Upgrades {
+    type Storage = VecStorage<Self>;
+}
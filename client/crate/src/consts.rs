@@ -1,2 +1,80 @@
 pub const HOST_URL: &str = "ws://localhost:8090";
 pub const SEND_STATE_DELAY: f64 = 0.25;
+pub const MIN_SEND_STATE_DELAY: f64 = 0.05;
+pub const IDLE_SEND_STATE_DELAY: f64 = 1.0;
+/// Bytes per second that player states may take.
+pub const STATE_BANDWIDTH: f64 = 512.0;
+pub const PLAYER_SPEED: f32 = 400.0;
+/// Half of Ferris sprite size.
+pub const PLAYER_RADIUS: f32 = 87.5;
+pub const COLLISION_CELL_SIZE: f32 = 256.0;
+/// Opacity of enemy that player overlaps and is older than, until server decides.
+pub const PREY_ALPHA: f32 = 0.5;
+pub const PICKUP_RADIUS: f32 = 32.0;
+/// How often collection of touched pickup is requested until server answers.
+pub const PICKUP_REQUEST_DELAY: f64 = 0.5;
+/// Pulses per second.
+pub const PICKUP_PULSE_RATE: f64 = 1.5;
+/// How much pickup grows and shrinks while pulsing.
+pub const PICKUP_PULSE_SCALE: f32 = 0.15;
+/// Time spent on every dash.
+pub const DASH_COST: f32 = 5.0;
+pub const DASH_DURATION: f64 = 0.25;
+pub const DASH_MULTIPLIER: f32 = 3.0;
+pub const DASH_COOLDOWN: f64 = 2.0;
+/// Player cannot dash if it would leave less time than this.
+pub const MIN_DASH_TIME: f32 = 10.0;
+/// How much dashing player is stretched.
+pub const DASH_SCALE: f32 = 1.2;
+/// Time price of first upgrade level, every next level costs this much more.
+pub const UPGRADE_BASE_COST: f32 = 10.0;
+pub const MAX_UPGRADE_LEVEL: u8 = 3;
+/// Fraction of base speed gained per level.
+pub const UPGRADE_SPEED_STEP: f32 = 0.15;
+/// Pickup reach gained per level.
+pub const UPGRADE_PICKUP_RADIUS_STEP: f32 = 50.0;
+/// Fraction of time decay removed per level.
+pub const UPGRADE_DECAY_STEP: f32 = 0.15;
+/// Seconds lost per second with constant time decay.
+pub const TIME_DECAY_RATE: f32 = 1.0;
+/// Extra decay per second of time left, 0.01 doubles it at 100 seconds.
+pub const TIME_DECAY_PROPORTIONAL: f32 = 0.01;
+/// Extra decay per second played, 0.01 doubles it after 100 seconds.
+pub const TIME_DECAY_ACCELERATING: f32 = 0.01;
+/// Seconds lost per second while moving with moving time decay.
+pub const TIME_DECAY_MOVING: f32 = 1.5;
+pub const CAMERA_SCALE: f32 = 1024.0;
+pub const PING_DELAY: f64 = 1.0;
+pub const INTERPOLATION_DELAY: f64 = 0.3;
+pub const MAX_EXTRAPOLATION: f64 = 0.25;
+pub const RECONCILIATION_TOLERANCE: f32 = 1.0;
+/// Inputs recorded between two sends go out together, same ones merged into one.
+pub const INPUT_SEND_DELAY: f64 = 0.05;
+/// Longest input server accepts (server `maxInputDelta`), merged inputs never exceed it.
+pub const MAX_INPUT_DELTA: f32 = 0.1;
+/// Predicted inputs kept for replay, oldest are forgotten if server never corrects them
+/// (e.g. older server).
+pub const MAX_INPUT_HISTORY: usize = 256;
+/// Defaults of network simulator, enabled with `simulate_network` setting or debug key.
+pub const SIMULATED_LATENCY: f64 = 0.1;
+pub const SIMULATED_JITTER: f64 = 0.05;
+pub const SIMULATED_LOSS: f64 = 0.05;
+pub const SIMULATED_DUPLICATE: f64 = 0.01;
+pub const SIMULATED_REORDER: f64 = 0.05;
+/// View area half extents relative to camera scale.
+pub const VIEW_AREA_SCALE: f32 = 1.0;
+/// Enemies are sent and kept until they are this far outside of view area, must match server.
+pub const VIEW_AREA_MARGIN: f32 = 256.0;
+pub const VIEW_AREA_DELAY: f64 = 0.5;
+/// How far view area center moves before it is sent again.
+pub const VIEW_AREA_TOLERANCE: f32 = 64.0;
+pub const PROTOCOL_VERSION: u32 = 3;
+pub const CLIENT_BUILD_ID: &str = env!("CARGO_PKG_VERSION");
+/// Client understands `MessageData::Batch` frames.
+pub const CAPABILITY_BATCH: u32 = 1;
+/// Client understands and sends `MessageData::PlayerStateCompact`.
+pub const CAPABILITY_COMPACT_STATE: u32 = 1 << 1;
+pub const CLIENT_CAPABILITIES: u32 = CAPABILITY_BATCH | CAPABILITY_COMPACT_STATE;
+pub const RECONNECT_GRACE: f64 = 15.0;
+pub const RECONNECT_BASE_DELAY: f64 = 0.5;
+pub const RECONNECT_MAX_DELAY: f64 = 4.0;
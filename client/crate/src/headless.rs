@@ -0,0 +1,87 @@
+use crate::{
+    components::{
+        collider::Collider, dash::Dash, enemy_controller::EnemyController, follow::Follow,
+        owned_by::OwnedBy, pickup::Pickup, player_controller::PlayerController,
+        send_policy::StateSendPolicy, snapshot_buffer::SnapshotBuffer, speed::Speed, time::Time,
+        time_decay::TimeDecay, upgrades::Upgrades, velocity::Velocity, EnemyTag, PlayerTag,
+        TimerTag,
+    },
+    net::GameClient,
+    resources::{
+        client_config::ClientConfig, collisions::Collisions, network_clock::NetworkClock,
+        network_stats::NetworkStats, shop::Shop,
+    },
+};
+use oxygengine::prelude::*;
+use std::time::Duration;
+
+/// Timer advancing by fixed step each frame, so headless runs are deterministic.
+pub struct FixedAppTimer {
+    delta_time: Duration,
+}
+
+impl FixedAppTimer {
+    pub fn new(delta_time: f64) -> Self {
+        Self {
+            delta_time: Duration::from_secs_f64(delta_time),
+        }
+    }
+}
+
+impl AppTimer for FixedAppTimer {
+    fn tick(&mut self) {}
+
+    fn delta_time(&self) -> Duration {
+        self.delta_time
+    }
+
+    fn delta_time_seconds(&self) -> f64 {
+        self.delta_time.as_secs_f64()
+    }
+}
+
+/// Creates world with game components and resources registered but without renderer,
+/// input devices or sockets attached, so systems can run natively (e.g. in tests).
+/// Network can only open clients to URLs with `MockHub` registered.
+pub fn headless_world(delta_time: f64) -> World {
+    let mut world = World::new();
+    world.register::<CompositeCamera>();
+    world.register::<CompositeRenderable>();
+    world.register::<CompositeRenderAlpha>();
+    world.register::<CompositeRenderDepth>();
+    world.register::<CompositeTransform>();
+    world.register::<Parent>();
+    world.register::<Tag>();
+    world.register::<Collider>();
+    world.register::<Dash>();
+    world.register::<EnemyController>();
+    world.register::<EnemyTag>();
+    world.register::<Follow>();
+    world.register::<OwnedBy>();
+    world.register::<Pickup>();
+    world.register::<PlayerController>();
+    world.register::<PlayerTag>();
+    world.register::<SnapshotBuffer>();
+    world.register::<Speed>();
+    world.register::<StateSendPolicy>();
+    world.register::<Time>();
+    world.register::<TimeDecay>();
+    world.register::<TimerTag>();
+    world.register::<Upgrades>();
+    world.register::<Velocity>();
+    add_headless_resources(&mut world, delta_time);
+    world
+}
+
+/// Adds resources used by game states and systems (network, timer, config, etc.)
+/// without registering any storage.
+pub fn add_headless_resources(world: &mut World, delta_time: f64) {
+    world.add_resource(AppLifeCycle::new(Box::new(FixedAppTimer::new(delta_time))));
+    world.add_resource(HierarchyChangeRes::default());
+    world.add_resource(Network::<GameClient>::new(0));
+    world.add_resource(ClientConfig::default());
+    world.add_resource(NetworkClock::default());
+    world.add_resource(NetworkStats::default());
+    world.add_resource(Collisions::default());
+    world.add_resource(Shop::default());
+}
@@ -1,29 +1,49 @@
 extern crate byteorder;
 extern crate oxygengine;
+extern crate serde;
+extern crate serde_json;
+extern crate your_time_is_currency_derive;
 
 #[macro_use]
 mod macros;
 
-mod components;
-mod consts;
-mod messages;
-mod states;
-mod systems;
+pub mod components;
+pub mod consts;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod headless;
+pub mod messages;
+pub mod net;
+pub mod resources;
+pub mod states;
+pub mod systems;
 
+#[cfg(target_arch = "wasm32")]
 use crate::{
+    net::GameClient,
+    resources::{
+        client_config::ClientConfig, collisions::Collisions, network_clock::NetworkClock,
+        network_stats::NetworkStats, shop::Shop,
+    },
     states::loading::LoadingState,
     systems::{
-        enemy_control::EnemyControlSystem, follow::FollowSystem,
-        player_control::PlayerControlSystem, time::TimeSystem,
+        collision::CollisionSystem, dash::DashSystem, follow::FollowSystem,
+        interpolation::InterpolationSystem, local_server::LocalServerSystem,
+        network_clock::NetworkClockSystem, network_simulator::NetworkSimulatorSystem,
+        network_stats::NetworkStatsSystem, pickup::PickupSystem,
+        player_control::PlayerControlSystem, reconciliation::ReconciliationSystem,
+        shop::ShopSystem, time::TimeSystem,
     },
 };
+#[cfg(target_arch = "wasm32")]
 use oxygengine::prelude::*;
+#[cfg(target_arch = "wasm32")]
 use wasm_bindgen::prelude::*;
 
-#[cfg(feature = "wee_alloc")]
+#[cfg(all(target_arch = "wasm32", feature = "wee_alloc"))]
 #[global_allocator]
 static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
 
+#[cfg(target_arch = "wasm32")]
 #[wasm_bindgen]
 pub fn run() -> Result<(), JsValue> {
     set_panic_hook();
@@ -41,8 +61,15 @@ pub fn run() -> Result<(), JsValue> {
             input.map_axis("move-down", "keyboard", "KeyS");
             input.map_axis("move-left", "keyboard", "KeyA");
             input.map_axis("move-right", "keyboard", "KeyD");
+            input.map_trigger("dash", "keyboard", "Space");
+            input.map_trigger("toggle-shop", "keyboard", "KeyB");
+            input.map_trigger("buy-speed", "keyboard", "Digit1");
+            input.map_trigger("buy-pickup-radius", "keyboard", "Digit2");
+            input.map_trigger("buy-decay-rate", "keyboard", "Digit3");
+            input.map_trigger("toggle-stats", "keyboard", "F3");
+            input.map_trigger("toggle-simulator", "keyboard", "F4");
         })
-        .with_bundle(oxygengine::network::bundle_installer::<WebClient, ()>, 0)
+        .with_bundle(oxygengine::network::bundle_installer::<GameClient, ()>, 0)
         .with_bundle(
             oxygengine::composite_renderer::bundle_installer,
             WebCompositeRenderer::with_state(
@@ -50,9 +77,31 @@ pub fn run() -> Result<(), JsValue> {
                 RenderState::new(Some(Color::rgb(11, 72, 107))),
             ),
         )
-        .with_system(PlayerControlSystem, "player_control", &[])
-        .with_system(EnemyControlSystem, "enemy_control", &[])
+        .with_resource(ClientConfig::default())
+        .with_resource(NetworkClock::default())
+        .with_resource(NetworkStats::default())
+        .with_resource(Collisions::default())
+        .with_resource(Shop::default())
+        .with_system(DashSystem, "dash", &[])
+        .with_system(PlayerControlSystem, "player_control", &["dash"])
+        .with_system(ReconciliationSystem, "reconciliation", &["player_control"])
+        .with_system(LocalServerSystem, "local_server", &[])
+        .with_system(NetworkClockSystem, "network_clock", &[])
+        .with_system(InterpolationSystem, "interpolation", &["network_clock"])
+        .with_system(NetworkStatsSystem, "network_stats", &[])
+        .with_system(NetworkSimulatorSystem, "network_simulator", &[])
+        .with_system(ShopSystem, "shop", &[])
         .with_system(FollowSystem, "follow", &[])
+        .with_system(
+            PickupSystem,
+            "pickup",
+            &["player_control", "reconciliation"],
+        )
+        .with_system(
+            CollisionSystem,
+            "collision",
+            &["player_control", "reconciliation", "interpolation"],
+        )
         .with_system(TimeSystem, "time", &[])
         .build(LoadingState::default(), WebAppTimer::default());
 
@@ -61,6 +110,7 @@ pub fn run() -> Result<(), JsValue> {
     Ok(())
 }
 
+#[cfg(target_arch = "wasm32")]
 fn set_panic_hook() {
     #[cfg(feature = "console_error_panic_hook")]
     console_error_panic_hook::set_once();
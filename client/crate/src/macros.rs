@@ -1,11 +1,18 @@
+#[cfg(target_arch = "wasm32")]
 use wasm_bindgen::prelude::*;
 
+#[cfg(target_arch = "wasm32")]
 #[wasm_bindgen]
 extern "C" {
     #[wasm_bindgen(js_namespace = console, js_name = log)]
     pub fn console_log(s: &str);
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+pub fn console_log(s: &str) {
+    println!("{}", s);
+}
+
 #[macro_export]
 macro_rules! console_log {
     ($($t:tt)*) => (console_log(&format_args!($($t)*).to_string()))
@@ -1,15 +1,112 @@
+use crate::consts::{CLIENT_BUILD_ID, CLIENT_CAPABILITIES, PROTOCOL_VERSION};
 use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
 use oxygengine::prelude::*;
-use std::io::{Cursor, Read, Write};
+use std::{
+    convert::TryFrom,
+    error::Error,
+    fmt,
+    io::{Cursor, Read, Write},
+};
+use your_time_is_currency_derive::Message;
 
-#[derive(Debug, Clone)]
+/// Version written into every frame header (server `version`).
+pub const MESSAGE_VERSION: u32 = 0;
+/// Id of `MessageData::Batch`.
+pub const BATCH_ID: u32 = 12;
+/// Size of frame header: message id and version, both big-endian u32.
+pub const HEADER_SIZE: usize = 8;
+/// Longest string (in bytes) decoder accepts, so frame cannot request huge allocation.
+pub const MAX_STRING_LENGTH: u32 = 1024;
+/// Longest list decoder accepts.
+pub const MAX_LIST_LENGTH: u32 = 1024;
+/// Size of arena grid cell used by `MsgPlayerStateCompact`.
+pub const COMPACT_CELL_SIZE: Scalar = 1024.0;
+/// Compact position steps per unit.
+pub const COMPACT_POSITION_STEPS: Scalar = 64.0;
+/// Compact speed steps per unit.
+pub const COMPACT_SPEED_STEPS: Scalar = 16.0;
+/// Compact time steps per second.
+pub const COMPACT_TIME_STEPS: Scalar = 10.0;
+/// Bit of `MsgPlayerState::flags` and `MsgPlayerInput::flags` set while player dashes.
+pub const PLAYER_FLAG_DASH: u8 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProtocolError {
+    /// Frame ended before all fields were read.
+    Truncated,
+    /// Player name is not a valid UTF-8 string.
+    InvalidName,
+    /// Message id is not known to this client.
+    UnknownMessage(u32),
+    /// Enum variant index or option flag is out of range.
+    UnknownVariant(u32),
+    /// Frame contains (count) bytes left after message was read.
+    TrailingBytes(usize),
+    /// Frame header carries version other than `MESSAGE_VERSION`.
+    UnknownVersion(u32),
+    /// Compact state is relative to state (sequence) that receiver does not have.
+    UnknownBaseline(u16),
+    /// Batch contains another batch.
+    NestedBatch,
+    /// String or list length is above decoder limit.
+    TooLong(u32),
+}
+
+impl fmt::Display for ProtocolError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ProtocolError::Truncated => write!(f, "truncated frame"),
+            ProtocolError::InvalidName => write!(f, "invalid UTF-8 name"),
+            ProtocolError::UnknownMessage(id) => write!(f, "unknown message id: {}", id),
+            ProtocolError::UnknownVariant(index) => write!(f, "unknown variant: {}", index),
+            ProtocolError::TrailingBytes(count) => write!(f, "{} trailing bytes", count),
+            ProtocolError::UnknownVersion(version) => {
+                write!(f, "unknown message version: {}", version)
+            }
+            ProtocolError::UnknownBaseline(sequence) => {
+                write!(f, "unknown baseline state: {}", sequence)
+            }
+            ProtocolError::NestedBatch => write!(f, "nested batch"),
+            ProtocolError::TooLong(length) => write!(f, "length {} is above limit", length),
+        }
+    }
+}
+
+impl Error for ProtocolError {}
+
+impl From<std::io::Error> for ProtocolError {
+    fn from(_: std::io::Error) -> Self {
+        ProtocolError::Truncated
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum MessageData {
-    Unknown,
-    InitPlayer(MsgPlayerInfo),
+    InitPlayer(MsgInitPlayer),
     NewPlayer(MsgPlayerInfo),
     PlayerState(MsgPlayerState),
     PlayerDisconnected(u32),
     PlayerEat(f32),
+    Hello(MsgHello),
+    Welcome(MsgWelcome),
+    PlayerInput(MsgPlayerInput),
+    PlayerCorrection(MsgPlayerCorrection),
+    /// Client local time when ping was sent.
+    Ping(f64),
+    Pong(MsgPong),
+    /// Many messages packed into one frame.
+    Batch(Vec<MessageData>),
+    PlayerStateCompact(MsgPlayerStateCompact),
+    /// Sequence of compact player state that was received.
+    PlayerStateAck(u16),
+    ViewArea(MsgViewArea),
+    PickupSpawned(MsgPickup),
+    PickupDespawned(u32),
+    /// Client asks to collect pickup it touches, server answers with `PlayerEat`.
+    CollectPickup(u32),
+    PurchaseUpgrade(MsgPurchaseUpgrade),
+    /// Server took upgrade price from player time, refused purchases get no answer.
+    UpgradePurchased(MsgUpgradePurchased),
 }
 
 impl MessageData {
@@ -20,155 +117,767 @@ impl MessageData {
             MessageData::PlayerState(_) => 3,
             MessageData::PlayerDisconnected(_) => 4,
             MessageData::PlayerEat(_) => 5,
-            _ => 0,
+            MessageData::Hello(_) => 6,
+            MessageData::Welcome(_) => 7,
+            MessageData::PlayerInput(_) => 8,
+            MessageData::PlayerCorrection(_) => 9,
+            MessageData::Ping(_) => 10,
+            MessageData::Pong(_) => 11,
+            MessageData::Batch(_) => BATCH_ID,
+            MessageData::PlayerStateCompact(_) => 13,
+            MessageData::PlayerStateAck(_) => 14,
+            MessageData::ViewArea(_) => 15,
+            MessageData::PickupSpawned(_) => 16,
+            MessageData::PickupDespawned(_) => 17,
+            MessageData::CollectPickup(_) => 18,
+            MessageData::PurchaseUpgrade(_) => 19,
+            MessageData::UpgradePurchased(_) => 20,
+        }
+    }
+
+    /// Returns batched messages, or just this message if it is not a batch.
+    pub fn unpack(self) -> Vec<Self> {
+        match self {
+            MessageData::Batch(messages) => messages,
+            message => vec![message],
         }
     }
+
+    /// Encodes whole frame, header included, as it travels through web socket.
+    pub fn to_frame(&self) -> Vec<u8> {
+        let mut frame = Vec::with_capacity(HEADER_SIZE);
+        frame.write_u32::<BigEndian>(self.id()).unwrap();
+        frame.write_u32::<BigEndian>(MESSAGE_VERSION).unwrap();
+        let data: Vec<u8> = self.clone().into();
+        frame.extend(data);
+        frame
+    }
+
+    /// Decodes message body (frame without header) of given message id.
+    fn read_body<R>(id: u32, stream: &mut R) -> Result<Self, ProtocolError>
+    where
+        R: Read,
+    {
+        let message = match id {
+            1 => MessageData::InitPlayer(MsgInitPlayer::msg_read(stream)?),
+            2 => MessageData::NewPlayer(MsgPlayerInfo::msg_read(stream)?),
+            3 => MessageData::PlayerState(MsgPlayerState::msg_read(stream)?),
+            4 => MessageData::PlayerDisconnected(u32::msg_read(stream)?),
+            5 => MessageData::PlayerEat(f32::msg_read(stream)?),
+            6 => MessageData::Hello(MsgHello::msg_read(stream)?),
+            7 => MessageData::Welcome(MsgWelcome::msg_read(stream)?),
+            8 => MessageData::PlayerInput(MsgPlayerInput::msg_read(stream)?),
+            9 => MessageData::PlayerCorrection(MsgPlayerCorrection::msg_read(stream)?),
+            10 => MessageData::Ping(f64::msg_read(stream)?),
+            11 => MessageData::Pong(MsgPong::msg_read(stream)?),
+            BATCH_ID => MessageData::Batch(Vec::<MessageData>::msg_read(stream)?),
+            13 => MessageData::PlayerStateCompact(MsgPlayerStateCompact::msg_read(stream)?),
+            14 => MessageData::PlayerStateAck(u16::msg_read(stream)?),
+            15 => MessageData::ViewArea(MsgViewArea::msg_read(stream)?),
+            16 => MessageData::PickupSpawned(MsgPickup::msg_read(stream)?),
+            17 => MessageData::PickupDespawned(u32::msg_read(stream)?),
+            18 => MessageData::CollectPickup(u32::msg_read(stream)?),
+            19 => MessageData::PurchaseUpgrade(MsgPurchaseUpgrade::msg_read(stream)?),
+            20 => MessageData::UpgradePurchased(MsgUpgradePurchased::msg_read(stream)?),
+            id => return Err(ProtocolError::UnknownMessage(id)),
+        };
+        Ok(message)
+    }
+
+    /// Decodes whole frame, header included.
+    pub fn from_frame(frame: &[u8]) -> Result<Self, ProtocolError> {
+        let stream = &mut Cursor::new(frame);
+        let id = stream.read_u32::<BigEndian>()?;
+        let version = stream.read_u32::<BigEndian>()?;
+        Self::try_from((MessageID::new(id, version), &frame[HEADER_SIZE..]))
+    }
 }
 
-impl From<(MessageID, &[u8])> for MessageData {
-    fn from((id, data): (MessageID, &[u8])) -> Self {
+impl TryFrom<(MessageID, &[u8])> for MessageData {
+    type Error = ProtocolError;
+
+    fn try_from((id, data): (MessageID, &[u8])) -> Result<Self, Self::Error> {
+        if id.version() != MESSAGE_VERSION {
+            return Err(ProtocolError::UnknownVersion(id.version()));
+        }
         let stream = &mut Cursor::new(data);
-        match id.id() {
-            1 => MessageData::InitPlayer(MsgPlayerInfo::msg_read(stream).unwrap()),
-            2 => MessageData::NewPlayer(MsgPlayerInfo::msg_read(stream).unwrap()),
-            3 => MessageData::PlayerState(MsgPlayerState::msg_read(stream).unwrap()),
-            4 => MessageData::PlayerDisconnected(stream.read_u32::<BigEndian>().unwrap()),
-            5 => MessageData::PlayerEat(stream.read_f32::<BigEndian>().unwrap()),
-            _ => MessageData::Unknown,
+        let result = MessageData::read_body(id.id(), stream)?;
+        let left = data.len() - stream.position() as usize;
+        if left > 0 {
+            Err(ProtocolError::TrailingBytes(left))
+        } else {
+            Ok(result)
         }
     }
 }
 
-impl Into<Vec<u8>> for MessageData {
-    fn into(self) -> Vec<u8> {
+impl From<MessageData> for Vec<u8> {
+    fn from(message: MessageData) -> Self {
         let mut stream = Cursor::new(vec![]);
-        match self {
+        match message {
+            MessageData::InitPlayer(init) => init.msg_write(&mut stream),
+            MessageData::NewPlayer(info) => info.msg_write(&mut stream),
             MessageData::PlayerState(state) => state.msg_write(&mut stream),
-            _ => {}
+            MessageData::PlayerDisconnected(id) => id.msg_write(&mut stream),
+            MessageData::PlayerEat(time) => time.msg_write(&mut stream),
+            MessageData::Hello(hello) => hello.msg_write(&mut stream),
+            MessageData::Welcome(welcome) => welcome.msg_write(&mut stream),
+            MessageData::PlayerInput(input) => input.msg_write(&mut stream),
+            MessageData::PlayerCorrection(correction) => correction.msg_write(&mut stream),
+            MessageData::Ping(time) => time.msg_write(&mut stream),
+            MessageData::Pong(pong) => pong.msg_write(&mut stream),
+            MessageData::Batch(messages) => messages.msg_write(&mut stream),
+            MessageData::PlayerStateCompact(state) => state.msg_write(&mut stream),
+            MessageData::PlayerStateAck(sequence) => sequence.msg_write(&mut stream),
+            MessageData::ViewArea(area) => area.msg_write(&mut stream),
+            MessageData::PickupSpawned(pickup) => pickup.msg_write(&mut stream),
+            MessageData::PickupDespawned(id) => id.msg_write(&mut stream),
+            MessageData::CollectPickup(id) => id.msg_write(&mut stream),
+            MessageData::PurchaseUpgrade(purchase) => purchase.msg_write(&mut stream),
+            MessageData::UpgradePurchased(purchase) => purchase.msg_write(&mut stream),
         }
         stream.into_inner()
     }
 }
 
 pub trait Message: Sized {
-    fn msg_read<R>(_stream: &mut R) -> Option<Self>
+    fn msg_read<R>(stream: &mut R) -> Result<Self, ProtocolError>
+    where
+        R: Read;
+
+    fn msg_write<W>(&self, stream: &mut W)
+    where
+        W: Write;
+}
+
+impl Message for u8 {
+    fn msg_read<R>(stream: &mut R) -> Result<Self, ProtocolError>
+    where
+        R: Read,
+    {
+        Ok(stream.read_u8()?)
+    }
+
+    fn msg_write<W>(&self, stream: &mut W)
+    where
+        W: Write,
+    {
+        stream.write_u8(*self).unwrap();
+    }
+}
+
+impl Message for u16 {
+    fn msg_read<R>(stream: &mut R) -> Result<Self, ProtocolError>
+    where
+        R: Read,
+    {
+        Ok(stream.read_u16::<BigEndian>()?)
+    }
+
+    fn msg_write<W>(&self, stream: &mut W)
+    where
+        W: Write,
+    {
+        stream.write_u16::<BigEndian>(*self).unwrap();
+    }
+}
+
+impl Message for i16 {
+    fn msg_read<R>(stream: &mut R) -> Result<Self, ProtocolError>
+    where
+        R: Read,
+    {
+        Ok(stream.read_i16::<BigEndian>()?)
+    }
+
+    fn msg_write<W>(&self, stream: &mut W)
+    where
+        W: Write,
+    {
+        stream.write_i16::<BigEndian>(*self).unwrap();
+    }
+}
+
+impl Message for u32 {
+    fn msg_read<R>(stream: &mut R) -> Result<Self, ProtocolError>
     where
         R: Read,
     {
-        None
+        Ok(stream.read_u32::<BigEndian>()?)
     }
 
-    fn msg_write<W>(&self, _stream: &mut W)
+    fn msg_write<W>(&self, stream: &mut W)
     where
         W: Write,
     {
+        stream.write_u32::<BigEndian>(*self).unwrap();
+    }
+}
+
+impl Message for f32 {
+    fn msg_read<R>(stream: &mut R) -> Result<Self, ProtocolError>
+    where
+        R: Read,
+    {
+        Ok(stream.read_f32::<BigEndian>()?)
+    }
+
+    fn msg_write<W>(&self, stream: &mut W)
+    where
+        W: Write,
+    {
+        stream.write_f32::<BigEndian>(*self).unwrap();
+    }
+}
+
+impl Message for f64 {
+    fn msg_read<R>(stream: &mut R) -> Result<Self, ProtocolError>
+    where
+        R: Read,
+    {
+        Ok(stream.read_f64::<BigEndian>()?)
+    }
+
+    fn msg_write<W>(&self, stream: &mut W)
+    where
+        W: Write,
+    {
+        stream.write_f64::<BigEndian>(*self).unwrap();
     }
 }
 
 impl Message for String {
-    fn msg_read<R>(stream: &mut R) -> Option<Self>
+    fn msg_read<R>(stream: &mut R) -> Result<Self, ProtocolError>
     where
-        R: Read + ReadBytesExt,
+        R: Read,
     {
-        let size = stream.read_u32::<BigEndian>().unwrap();
-        let mut buff = vec![0; size as usize];
-        stream.read_exact(&mut buff).unwrap();
-        Some(String::from_utf8(buff).unwrap())
+        let size = stream.read_u32::<BigEndian>()?;
+        if size > MAX_STRING_LENGTH {
+            return Err(ProtocolError::TooLong(size));
+        }
+        // buffer grows with bytes actually read, not with length frame claims.
+        let mut buff = vec![];
+        stream.take(u64::from(size)).read_to_end(&mut buff)?;
+        if buff.len() < size as usize {
+            return Err(ProtocolError::Truncated);
+        }
+        String::from_utf8(buff).map_err(|_| ProtocolError::InvalidName)
     }
 
     fn msg_write<W>(&self, stream: &mut W)
     where
-        W: Write + WriteBytesExt,
+        W: Write,
     {
         let bytes = self.as_bytes();
         stream.write_u32::<BigEndian>(bytes.len() as u32).unwrap();
-        stream.write(bytes).unwrap();
+        stream.write_all(bytes).unwrap();
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct MsgPlayerInfo {
-    pub id: u32,
-    pub name: String,
-    pub time: f32,
-    pub position: Vec2,
+impl Message for Vec2 {
+    fn msg_read<R>(stream: &mut R) -> Result<Self, ProtocolError>
+    where
+        R: Read,
+    {
+        let x = stream.read_f32::<BigEndian>()?;
+        let y = stream.read_f32::<BigEndian>()?;
+        Ok(Vec2::new(x, y))
+    }
+
+    fn msg_write<W>(&self, stream: &mut W)
+    where
+        W: Write,
+    {
+        stream.write_f32::<BigEndian>(self.x).unwrap();
+        stream.write_f32::<BigEndian>(self.y).unwrap();
+    }
 }
 
-impl Message for MsgPlayerInfo {
-    fn msg_read<R>(stream: &mut R) -> Option<Self>
+impl<T> Message for Vec<T>
+where
+    T: Message,
+{
+    fn msg_read<R>(stream: &mut R) -> Result<Self, ProtocolError>
     where
-        R: Read + ReadBytesExt,
+        R: Read,
     {
-        let id = stream.read_u32::<BigEndian>().unwrap();
-        let name = String::msg_read(stream).unwrap();
-        let time = stream.read_f32::<BigEndian>().unwrap();
-        let position = {
-            let x = stream.read_f32::<BigEndian>().unwrap();
-            let y = stream.read_f32::<BigEndian>().unwrap();
-            Vec2::new(x, y)
-        };
-        Some(Self {
-            id,
-            name,
-            time,
-            position,
-        })
+        let size = stream.read_u32::<BigEndian>()?;
+        if size > MAX_LIST_LENGTH {
+            return Err(ProtocolError::TooLong(size));
+        }
+        (0..size).map(|_| T::msg_read(stream)).collect()
     }
 
     fn msg_write<W>(&self, stream: &mut W)
     where
-        W: Write + WriteBytesExt,
+        W: Write,
     {
-        stream.write_u32::<BigEndian>(self.id).unwrap();
-        self.name.msg_write(stream);
-        stream.write_f32::<BigEndian>(self.time).unwrap();
-        stream.write_f32::<BigEndian>(self.position.x).unwrap();
-        stream.write_f32::<BigEndian>(self.position.y).unwrap();
+        stream.write_u32::<BigEndian>(self.len() as u32).unwrap();
+        for item in self {
+            item.msg_write(stream);
+        }
+    }
+}
+
+/// Stored as `u32` flag (0 or 1) followed by value if present.
+impl<T> Message for Option<T>
+where
+    T: Message,
+{
+    fn msg_read<R>(stream: &mut R) -> Result<Self, ProtocolError>
+    where
+        R: Read,
+    {
+        match stream.read_u32::<BigEndian>()? {
+            0 => Ok(None),
+            1 => Ok(Some(T::msg_read(stream)?)),
+            flag => Err(ProtocolError::UnknownVariant(flag)),
+        }
+    }
+
+    fn msg_write<W>(&self, stream: &mut W)
+    where
+        W: Write,
+    {
+        match self {
+            Some(value) => {
+                stream.write_u32::<BigEndian>(1).unwrap();
+                value.msg_write(stream);
+            }
+            None => stream.write_u32::<BigEndian>(0).unwrap(),
+        }
     }
 }
 
-#[derive(Debug, Clone)]
+/// Stored as `u32` frame size followed by whole frame (header included), as batch item.
+impl Message for MessageData {
+    fn msg_read<R>(stream: &mut R) -> Result<Self, ProtocolError>
+    where
+        R: Read,
+    {
+        let size = u64::from(stream.read_u32::<BigEndian>()?);
+        if size < HEADER_SIZE as u64 {
+            return Err(ProtocolError::Truncated);
+        }
+        let id = stream.read_u32::<BigEndian>()?;
+        let version = stream.read_u32::<BigEndian>()?;
+        if version != MESSAGE_VERSION {
+            return Err(ProtocolError::UnknownVersion(version));
+        }
+        // refused before decoding, so hostile frame cannot nest batches deeply.
+        if id == BATCH_ID {
+            return Err(ProtocolError::NestedBatch);
+        }
+        // item is decoded straight from stream, limited to its size (as trait object, so
+        // nested readers do not make types recurse).
+        let mut item = stream.take(size - HEADER_SIZE as u64);
+        let message = MessageData::read_body(id, &mut (&mut item as &mut dyn Read))?;
+        let left = item.limit();
+        if left > 0 {
+            let skipped = std::io::copy(&mut item, &mut std::io::sink())?;
+            return Err(if skipped < left {
+                ProtocolError::Truncated
+            } else {
+                ProtocolError::TrailingBytes(left as usize)
+            });
+        }
+        Ok(message)
+    }
+
+    fn msg_write<W>(&self, stream: &mut W)
+    where
+        W: Write,
+    {
+        let frame = self.to_frame();
+        stream.write_u32::<BigEndian>(frame.len() as u32).unwrap();
+        stream.write_all(&frame).unwrap();
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Message)]
+pub struct MsgPlayerInfo {
+    pub id: u32,
+    pub name: String,
+    pub time: f32,
+    pub position: Vec2,
+}
+
+#[derive(Debug, Clone, PartialEq, Message)]
+pub struct MsgInitPlayer {
+    pub info: MsgPlayerInfo,
+    /// Lets client take over this player after reconnecting.
+    pub resume_token: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Message)]
 pub struct MsgPlayerState {
     pub id: u32,
     pub time: f32,
     pub position: Vec2,
     pub velocity: Vec2,
+    /// Server time when this state was sampled.
+    pub timestamp: f64,
+    /// Bit set of `PLAYER_FLAG_*`.
+    pub flags: u8,
+}
+
+/// Quantized `MsgPlayerState`, optionally relative to earlier (baseline) state.
+/// Fields that are `None` did not change since baseline.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MsgPlayerStateCompact {
+    pub id: u32,
+    /// Never 0, wraps around.
+    pub sequence: u16,
+    /// Sequence of state this one is relative to, 0 if it is absolute.
+    pub baseline: u16,
+    /// Server time in milliseconds.
+    pub timestamp: u32,
+    /// Time left in tenths of second.
+    pub time: Option<u16>,
+    /// Arena grid cell that contains position.
+    pub cell: Option<(i16, i16)>,
+    /// Position inside grid cell in `1 / COMPACT_POSITION_STEPS` units.
+    pub offset: Option<(u16, u16)>,
+    /// Direction in `1 / 256` of full angle and speed in `1 / COMPACT_SPEED_STEPS` units.
+    pub velocity: Option<(u8, u16)>,
+    /// Bit set of `PLAYER_FLAG_*`.
+    pub flags: Option<u8>,
+}
+
+fn round_clamped(value: f64, max: f64) -> f64 {
+    value.round().max(0.0).min(max)
 }
 
-impl Message for MsgPlayerState {
-    fn msg_read<R>(stream: &mut R) -> Option<Self>
+impl MsgPlayerStateCompact {
+    const TIME: u8 = 1;
+    const CELL: u8 = 1 << 1;
+    const OFFSET: u8 = 1 << 2;
+    const VELOCITY: u8 = 1 << 3;
+    const FLAGS: u8 = 1 << 4;
+
+    /// Absolute compact state.
+    pub fn quantize(state: &MsgPlayerState, sequence: u16) -> Self {
+        let cell = |value: Scalar| {
+            (value / COMPACT_CELL_SIZE)
+                .floor()
+                .max(Scalar::from(i16::MIN))
+                .min(Scalar::from(i16::MAX)) as i16
+        };
+        let offset = |value: Scalar, cell: i16| {
+            let local = f64::from(value - Scalar::from(cell) * COMPACT_CELL_SIZE);
+            round_clamped(
+                local * f64::from(COMPACT_POSITION_STEPS),
+                f64::from(u16::MAX),
+            ) as u16
+        };
+        let cell_x = cell(state.position.x);
+        let cell_y = cell(state.position.y);
+        let speed = state.velocity.magnitude();
+        let direction = if speed > 0.0 {
+            let angle = f64::from(state.velocity.y.atan2(state.velocity.x));
+            ((angle / (std::f64::consts::PI * 2.0) * 256.0).round() as i32).rem_euclid(256) as u8
+        } else {
+            0
+        };
+        Self {
+            id: state.id,
+            sequence,
+            baseline: 0,
+            timestamp: round_clamped(state.timestamp * 1000.0, f64::from(u32::MAX)) as u32,
+            time: Some(round_clamped(
+                f64::from(state.time * COMPACT_TIME_STEPS),
+                f64::from(u16::MAX),
+            ) as u16),
+            cell: Some((cell_x, cell_y)),
+            offset: Some((
+                offset(state.position.x, cell_x),
+                offset(state.position.y, cell_y),
+            )),
+            velocity: Some((
+                direction,
+                round_clamped(f64::from(speed * COMPACT_SPEED_STEPS), f64::from(u16::MAX)) as u16,
+            )),
+            flags: Some(state.flags),
+        }
+    }
+
+    /// Leaves out fields that are same as in absolute `baseline`.
+    pub fn delta(&self, baseline: &Self) -> Self {
+        fn changed<T: PartialEq + Copy>(value: Option<T>, baseline: Option<T>) -> Option<T> {
+            if value == baseline {
+                None
+            } else {
+                value
+            }
+        }
+        Self {
+            id: self.id,
+            sequence: self.sequence,
+            baseline: baseline.sequence,
+            timestamp: self.timestamp,
+            time: changed(self.time, baseline.time),
+            cell: changed(self.cell, baseline.cell),
+            offset: changed(self.offset, baseline.offset),
+            velocity: changed(self.velocity, baseline.velocity),
+            flags: changed(self.flags, baseline.flags),
+        }
+    }
+
+    /// Turns state into absolute one, filling missing fields from absolute `baseline`.
+    pub fn resolve(&self, baseline: Option<&Self>) -> Result<Self, ProtocolError> {
+        if self.baseline == 0 {
+            return Ok(self.clone());
+        }
+        let baseline = match baseline {
+            Some(baseline) if baseline.sequence == self.baseline => baseline,
+            _ => return Err(ProtocolError::UnknownBaseline(self.baseline)),
+        };
+        Ok(Self {
+            id: self.id,
+            sequence: self.sequence,
+            baseline: 0,
+            timestamp: self.timestamp,
+            time: self.time.or(baseline.time),
+            cell: self.cell.or(baseline.cell),
+            offset: self.offset.or(baseline.offset),
+            velocity: self.velocity.or(baseline.velocity),
+            flags: self.flags.or(baseline.flags),
+        })
+    }
+
+    /// Converts absolute compact state back into full one.
+    pub fn to_state(&self) -> MsgPlayerState {
+        let (cell_x, cell_y) = self.cell.unwrap_or_default();
+        let (offset_x, offset_y) = self.offset.unwrap_or_default();
+        let (direction, speed) = self.velocity.unwrap_or_default();
+        let position = |cell: i16, offset: u16| {
+            Scalar::from(cell) * COMPACT_CELL_SIZE + Scalar::from(offset) / COMPACT_POSITION_STEPS
+        };
+        let angle = Scalar::from(direction) / 256.0 * std::f32::consts::PI * 2.0;
+        let speed = Scalar::from(speed) / COMPACT_SPEED_STEPS;
+        MsgPlayerState {
+            id: self.id,
+            time: Scalar::from(self.time.unwrap_or_default()) / COMPACT_TIME_STEPS,
+            position: Vec2::new(position(cell_x, offset_x), position(cell_y, offset_y)),
+            velocity: if speed > 0.0 {
+                Vec2::new(angle.cos(), angle.sin()) * speed
+            } else {
+                Vec2::zero()
+            },
+            timestamp: f64::from(self.timestamp) / 1000.0,
+            flags: self.flags.unwrap_or_default(),
+        }
+    }
+}
+
+/// Stored as id, sequence, baseline, timestamp and `u8` bit mask of fields that follow.
+impl Message for MsgPlayerStateCompact {
+    fn msg_read<R>(stream: &mut R) -> Result<Self, ProtocolError>
     where
-        R: Read + ReadBytesExt,
+        R: Read,
     {
-        let id = stream.read_u32::<BigEndian>().unwrap();
-        let time = stream.read_f32::<BigEndian>().unwrap();
-        let position = {
-            let x = stream.read_f32::<BigEndian>().unwrap();
-            let y = stream.read_f32::<BigEndian>().unwrap();
-            Vec2::new(x, y)
+        let id = u32::msg_read(stream)?;
+        let sequence = u16::msg_read(stream)?;
+        let baseline = u16::msg_read(stream)?;
+        let timestamp = u32::msg_read(stream)?;
+        let fields = u8::msg_read(stream)?;
+        if fields & !(Self::TIME | Self::CELL | Self::OFFSET | Self::VELOCITY | Self::FLAGS) != 0 {
+            return Err(ProtocolError::UnknownVariant(u32::from(fields)));
+        }
+        let time = if fields & Self::TIME != 0 {
+            Some(u16::msg_read(stream)?)
+        } else {
+            None
         };
-        let velocity = {
-            let x = stream.read_f32::<BigEndian>().unwrap();
-            let y = stream.read_f32::<BigEndian>().unwrap();
-            Vec2::new(x, y)
+        let cell = if fields & Self::CELL != 0 {
+            Some((i16::msg_read(stream)?, i16::msg_read(stream)?))
+        } else {
+            None
         };
-        Some(Self {
+        let offset = if fields & Self::OFFSET != 0 {
+            Some((u16::msg_read(stream)?, u16::msg_read(stream)?))
+        } else {
+            None
+        };
+        let velocity = if fields & Self::VELOCITY != 0 {
+            Some((u8::msg_read(stream)?, u16::msg_read(stream)?))
+        } else {
+            None
+        };
+        let flags = if fields & Self::FLAGS != 0 {
+            Some(u8::msg_read(stream)?)
+        } else {
+            None
+        };
+        Ok(Self {
             id,
+            sequence,
+            baseline,
+            timestamp,
             time,
-            position,
+            cell,
+            offset,
             velocity,
+            flags,
         })
     }
 
     fn msg_write<W>(&self, stream: &mut W)
     where
-        W: Write + WriteBytesExt,
+        W: Write,
     {
-        stream.write_u32::<BigEndian>(self.id).unwrap();
-        stream.write_f32::<BigEndian>(self.time).unwrap();
-        stream.write_f32::<BigEndian>(self.position.x).unwrap();
-        stream.write_f32::<BigEndian>(self.position.y).unwrap();
-        stream.write_f32::<BigEndian>(self.velocity.x).unwrap();
-        stream.write_f32::<BigEndian>(self.velocity.y).unwrap();
+        let mut fields = 0;
+        if self.time.is_some() {
+            fields |= Self::TIME;
+        }
+        if self.cell.is_some() {
+            fields |= Self::CELL;
+        }
+        if self.offset.is_some() {
+            fields |= Self::OFFSET;
+        }
+        if self.velocity.is_some() {
+            fields |= Self::VELOCITY;
+        }
+        if self.flags.is_some() {
+            fields |= Self::FLAGS;
+        }
+        self.id.msg_write(stream);
+        self.sequence.msg_write(stream);
+        self.baseline.msg_write(stream);
+        self.timestamp.msg_write(stream);
+        fields.msg_write(stream);
+        if let Some(time) = self.time {
+            time.msg_write(stream);
+        }
+        if let Some((x, y)) = self.cell {
+            x.msg_write(stream);
+            y.msg_write(stream);
+        }
+        if let Some((x, y)) = self.offset {
+            x.msg_write(stream);
+            y.msg_write(stream);
+        }
+        if let Some((direction, speed)) = self.velocity {
+            direction.msg_write(stream);
+            speed.msg_write(stream);
+        }
+        if let Some(flags) = self.flags {
+            flags.msg_write(stream);
+        }
+    }
+}
+
+/// Sent by client in reply to `MsgWelcome`.
+#[derive(Debug, Clone, PartialEq, Message)]
+pub struct MsgHello {
+    pub protocol_version: u32,
+    pub build_id: String,
+    pub capabilities: u32,
+    pub resume_token: Option<String>,
+}
+
+impl MsgHello {
+    pub fn new(resume_token: Option<String>) -> Self {
+        Self {
+            protocol_version: PROTOCOL_VERSION,
+            build_id: CLIENT_BUILD_ID.to_owned(),
+            capabilities: CLIENT_CAPABILITIES,
+            resume_token,
+        }
+    }
+}
+
+/// Sent by server right after connection is established.
+#[derive(Debug, Clone, PartialEq, Message)]
+pub struct MsgWelcome {
+    pub protocol_version: u32,
+    pub capabilities: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Message)]
+pub struct MsgPlayerInput {
+    pub sequence: u32,
+    pub direction: Vec2,
+    pub delta_time: f32,
+    /// Bit set of `PLAYER_FLAG_*`, dash is applied only if server allows it.
+    pub flags: u8,
+}
+
+/// Authoritative player state after server applied input with given sequence.
+#[derive(Debug, Clone, PartialEq, Message)]
+pub struct MsgPlayerCorrection {
+    pub sequence: u32,
+    pub position: Vec2,
+    pub velocity: Vec2,
+}
+
+#[derive(Debug, Clone, PartialEq, Message)]
+pub struct MsgPong {
+    /// Client local time copied from ping.
+    pub client_time: f64,
+    pub server_time: f64,
+}
+
+/// Part of arena client wants to receive players from.
+#[derive(Debug, Clone, PartialEq, Message)]
+pub struct MsgViewArea {
+    pub center: Vec2,
+    pub half_extents: Vec2,
+}
+
+impl MsgViewArea {
+    /// Tells if point lies inside area grown by `margin` on each side.
+    pub fn contains(&self, point: Vec2, margin: Scalar) -> bool {
+        (point.x - self.center.x).abs() <= self.half_extents.x + margin
+            && (point.y - self.center.y).abs() <= self.half_extents.y + margin
+    }
+}
+
+/// Time orb lying in arena.
+#[derive(Debug, Clone, PartialEq, Message)]
+pub struct MsgPickup {
+    pub id: u32,
+    /// Time given to player that collects it.
+    pub time: f32,
+    pub position: Vec2,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Message)]
+pub enum UpgradeKind {
+    Speed,
+    PickupRadius,
+    DecayRate,
+}
+
+impl UpgradeKind {
+    pub const ALL: [UpgradeKind; 3] = [
+        UpgradeKind::Speed,
+        UpgradeKind::PickupRadius,
+        UpgradeKind::DecayRate,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            UpgradeKind::Speed => "Speed",
+            UpgradeKind::PickupRadius => "Pickup radius",
+            UpgradeKind::DecayRate => "Slower decay",
+        }
     }
 }
+
+/// Asks server to sell next upgrade level, paid with player time.
+#[derive(Debug, Clone, PartialEq, Message)]
+pub struct MsgPurchaseUpgrade {
+    pub kind: UpgradeKind,
+    /// Level being bought, so repeated request does not buy it twice.
+    pub level: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Message)]
+pub struct MsgUpgradePurchased {
+    pub kind: UpgradeKind,
+    pub level: u8,
+    /// Time taken from player.
+    pub cost: f32,
+}
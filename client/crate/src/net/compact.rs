@@ -0,0 +1,39 @@
+use crate::messages::{MsgPlayerState, MsgPlayerStateCompact};
+use std::collections::VecDeque;
+
+/// How many unacknowledged states are kept as possible baselines; matches how many states
+/// server keeps as baselines (`maxCompactBaselines`).
+pub const MAX_UNACKNOWLEDGED: usize = 32;
+
+/// Encodes own player states relative to last state acknowledged by server.
+#[derive(Debug, Default, Clone)]
+pub struct StateEncoder {
+    sequence: u16,
+    sent: VecDeque<MsgPlayerStateCompact>,
+    acknowledged: Option<MsgPlayerStateCompact>,
+}
+
+impl StateEncoder {
+    pub fn encode(&mut self, state: &MsgPlayerState) -> MsgPlayerStateCompact {
+        // sequence 0 is reserved for absolute states.
+        self.sequence = self.sequence.wrapping_add(1).max(1);
+        let absolute = MsgPlayerStateCompact::quantize(state, self.sequence);
+        if self.sent.len() >= MAX_UNACKNOWLEDGED {
+            self.sent.pop_front();
+            // server may have already pruned acknowledged state, so send absolute states
+            // until one of them gets acknowledged.
+            self.acknowledged = None;
+        }
+        self.sent.push_back(absolute.clone());
+        match &self.acknowledged {
+            Some(baseline) => absolute.delta(baseline),
+            None => absolute,
+        }
+    }
+
+    pub fn acknowledge(&mut self, sequence: u16) {
+        if let Some(index) = self.sent.iter().position(|s| s.sequence == sequence) {
+            self.acknowledged = self.sent.drain(..=index).next_back();
+        }
+    }
+}
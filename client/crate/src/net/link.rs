@@ -0,0 +1,116 @@
+use oxygengine::prelude::*;
+
+/// Deterministic xorshift64 random generator, same seed gives same sequence.
+#[derive(Debug, Copy, Clone)]
+pub struct Random(u64);
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        // xorshift gets stuck at 0.
+        Random(seed.max(1))
+    }
+
+    /// Returns number in range 0 - 1 (exclusive).
+    pub fn sample(&mut self) -> f64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Bad network conditions applied to frames travelling both ways.
+#[derive(Debug, Copy, Clone)]
+pub struct LinkConditions {
+    /// Base one-way delay in seconds.
+    pub latency: f64,
+    /// Random extra one-way delay in seconds.
+    pub jitter: f64,
+    /// Chance (0 - 1) of frame being lost.
+    pub loss: f64,
+    /// Chance (0 - 1) of frame being delivered twice.
+    pub duplicate: f64,
+    /// Chance (0 - 1) of frame being held back so later frames overtake it.
+    pub reorder: f64,
+    /// Seed of random generator, same seed gives same run.
+    pub seed: u64,
+}
+
+impl Default for LinkConditions {
+    fn default() -> Self {
+        Self {
+            latency: 0.0,
+            jitter: 0.0,
+            loss: 0.0,
+            duplicate: 0.0,
+            reorder: 0.0,
+            seed: 1,
+        }
+    }
+}
+
+impl LinkConditions {
+    /// Returns delivery times of frame sent at given time: none if it gets lost, two if it gets
+    /// duplicated.
+    pub fn schedule(&self, random: &mut Random, time: f64) -> Vec<f64> {
+        if random.sample() < self.loss {
+            return vec![];
+        }
+        let copies = if random.sample() < self.duplicate {
+            2
+        } else {
+            1
+        };
+        (0..copies)
+            .map(|_| {
+                let mut delay = self.latency + random.sample() * self.jitter;
+                if random.sample() < self.reorder {
+                    delay += self.latency + self.jitter;
+                }
+                time + delay
+            })
+            .collect()
+    }
+}
+
+struct Frame {
+    deliver_at: f64,
+    id: MessageID,
+    data: Vec<u8>,
+}
+
+/// Frames travelling one way, delivered in order of their delivery times.
+#[derive(Default)]
+pub struct FrameQueue(Vec<Frame>);
+
+impl FrameQueue {
+    pub fn push(&mut self, deliver_at: f64, id: MessageID, data: Vec<u8>) {
+        self.0.push(Frame {
+            deliver_at,
+            id,
+            data,
+        });
+    }
+
+    /// Delivery time of earliest frame that is due at given time.
+    pub fn next_due(&self, time: f64) -> Option<f64> {
+        self.0
+            .iter()
+            .map(|frame| frame.deliver_at)
+            .filter(|deliver_at| *deliver_at <= time)
+            .min_by(|a, b| a.partial_cmp(b).unwrap())
+    }
+
+    /// Removes earliest frame that is due at given time.
+    pub fn take_due(&mut self, time: f64) -> Option<(MessageID, Vec<u8>)> {
+        let index = self
+            .0
+            .iter()
+            .enumerate()
+            .filter(|(_, frame)| frame.deliver_at <= time)
+            .min_by(|a, b| a.1.deliver_at.partial_cmp(&b.1.deliver_at).unwrap())
+            .map(|(index, _)| index)?;
+        let frame = self.0.remove(index);
+        Some((frame.id, frame.data))
+    }
+}
@@ -0,0 +1,591 @@
+use crate::{
+    components::{dash::Dash, upgrades::Upgrades},
+    consts::{MAX_INPUT_DELTA, PROTOCOL_VERSION, VIEW_AREA_MARGIN},
+    messages::{
+        MessageData, MsgHello, MsgInitPlayer, MsgPickup, MsgPlayerCorrection, MsgPlayerInfo,
+        MsgPlayerInput, MsgPlayerState, MsgPong, MsgPurchaseUpgrade, MsgUpgradePurchased,
+        MsgViewArea, MsgWelcome, PLAYER_FLAG_DASH,
+    },
+    net::{
+        link::Random,
+        mock::{MockOutbox, MockServer},
+    },
+    resources::client_config::ClientConfig,
+};
+use oxygengine::prelude::*;
+use std::collections::HashMap;
+
+pub const LOCAL_SERVER_URL: &str = "local://";
+
+const SPAWN_RANGE: Scalar = 400.0;
+const INIT_LIFETIME: f32 = 60.0;
+const COLLISION_RADIUS: Scalar = 100.0;
+const INIT_DELAY: f64 = 1.0;
+const NEW_PLAYER_INTERVAL: f64 = 1.0;
+const COLLISION_INTERVAL: f64 = 0.1;
+const CORRECTION_INTERVAL: f64 = 0.1;
+const RESUME_GRACE: f64 = 15.0;
+const MAX_PICKUPS: usize = 32;
+const PICKUP_INTERVAL: f64 = 1.0;
+const PICKUP_RANGE: Scalar = 1500.0;
+const PICKUP_TIMES: &[f32] = &[5.0, 10.0, 15.0];
+const PICKUP_LIFETIME: f64 = 30.0;
+/// Distance between player and pickup centers that still counts as touching (with lag).
+const PICKUP_REACH: Scalar = 150.0;
+/// Dash cooldown is checked against arrival of inputs, which jitter makes come closer.
+const DASH_COOLDOWN_SLACK: f64 = 0.25;
+const NAMES: &[&str] = &[
+    "Ada", "Alan", "Barbara", "Dennis", "Edsger", "Grace", "John", "Ken", "Linus", "Margaret",
+    "Niklaus", "Radia", "Tony", "Yukihiro",
+];
+
+struct Player {
+    connection: u32,
+    id: u32,
+    name: String,
+    time: f32,
+    position: Vec2,
+    velocity: Vec2,
+    joined: bool,
+    token: String,
+    sequence: u32,
+    corrected: bool,
+    /// Input time left of current dash.
+    dash: f32,
+    /// Server time when player may dash again.
+    dash_ready_at: f64,
+    upgrades: Upgrades,
+    /// Time left until player gets list of other players.
+    init_timer: Option<f64>,
+    /// Area player wants other players from, `None` means everywhere.
+    view: Option<MsgViewArea>,
+}
+
+impl Player {
+    fn sees(&self, position: Vec2) -> bool {
+        self.view
+            .as_ref()
+            .map(|view| view.contains(position, VIEW_AREA_MARGIN))
+            .unwrap_or(true)
+    }
+
+    fn info(&self) -> MsgPlayerInfo {
+        MsgPlayerInfo {
+            id: self.id,
+            name: self.name.clone(),
+            time: self.time,
+            position: self.position,
+        }
+    }
+}
+
+struct Session {
+    id: u32,
+    name: String,
+    time: f32,
+    position: Vec2,
+    upgrades: Upgrades,
+    expires_at: f64,
+}
+
+struct Pickup {
+    info: MsgPickup,
+    expires_at: f64,
+}
+
+/// In-process implementation of `server/index.js` game rules, so game can be played offline
+/// and tested end-to-end without Node.
+pub struct LocalServer {
+    time: f64,
+    random: Random,
+    next_id: u32,
+    players: Vec<Player>,
+    sessions: HashMap<String, Session>,
+    pickups: Vec<Pickup>,
+    next_pickup_id: u32,
+    new_player_timer: f64,
+    pickup_timer: f64,
+    collision_timer: f64,
+    correction_timer: f64,
+    player_speed: Scalar,
+    /// Dash rules, its state is not used.
+    dash: Dash,
+}
+
+impl Default for LocalServer {
+    fn default() -> Self {
+        Self::new(1)
+    }
+}
+
+impl LocalServer {
+    pub fn new(seed: u64) -> Self {
+        Self::with_config(seed, &ClientConfig::default())
+    }
+
+    /// Moves players with same speed and dash rules client predicts them with.
+    pub fn with_config(seed: u64, config: &ClientConfig) -> Self {
+        Self {
+            time: 0.0,
+            random: Random::new(seed),
+            next_id: 1,
+            players: vec![],
+            sessions: Default::default(),
+            pickups: vec![],
+            next_pickup_id: 1,
+            new_player_timer: NEW_PLAYER_INTERVAL,
+            pickup_timer: PICKUP_INTERVAL,
+            collision_timer: COLLISION_INTERVAL,
+            correction_timer: CORRECTION_INTERVAL,
+            player_speed: config.player_speed,
+            dash: config.dash(),
+        }
+    }
+
+    pub fn players_count(&self) -> usize {
+        self.players.iter().filter(|player| player.joined).count()
+    }
+
+    fn spawn_pickup(&mut self, time: f32, position: Vec2, outbox: &mut MockOutbox) {
+        let info = MsgPickup {
+            id: self.next_pickup_id,
+            time,
+            position,
+        };
+        self.next_pickup_id += 1;
+        self.send_all(MessageData::PickupSpawned(info.clone()), None, outbox);
+        self.pickups.push(Pickup {
+            info,
+            expires_at: self.time + PICKUP_LIFETIME,
+        });
+    }
+
+    fn random(&mut self) -> f64 {
+        self.random.sample()
+    }
+
+    fn players_collide(a: Vec2, b: Vec2) -> bool {
+        (a - b).sqr_magnitude() <= COLLISION_RADIUS * COLLISION_RADIUS
+    }
+
+    fn find_init_position(&mut self) -> Vec2 {
+        let joined = self
+            .players
+            .iter()
+            .filter(|player| player.joined)
+            .map(|player| player.position)
+            .collect::<Vec<_>>();
+        if joined.is_empty() {
+            return Vec2::zero();
+        }
+        let nearby = joined[(self.random() * joined.len() as f64) as usize % joined.len()];
+        let mut position = nearby;
+        for _ in 0..5 {
+            let direction = (self.random() * std::f64::consts::PI * 2.0) as Scalar;
+            position = nearby + Vec2::new(direction.cos(), direction.sin()) * SPAWN_RANGE;
+            if !Self::players_collide(position, nearby) {
+                break;
+            }
+        }
+        position
+    }
+
+    fn player_mut(&mut self, connection: u32) -> Option<&mut Player> {
+        self.players
+            .iter_mut()
+            .find(|player| player.connection == connection)
+    }
+
+    fn send_all(&self, message: MessageData, exclude: Option<u32>, outbox: &mut MockOutbox) {
+        for player in &self.players {
+            if player.joined && Some(player.connection) != exclude {
+                outbox.send(player.connection, message.clone());
+            }
+        }
+    }
+
+    /// Sends message about player to others that have it in their view area.
+    fn send_nearby(&self, message: MessageData, connection: u32, outbox: &mut MockOutbox) {
+        let position = match self.players.iter().find(|p| p.connection == connection) {
+            Some(player) => player.position,
+            None => return,
+        };
+        for player in &self.players {
+            if player.joined && player.connection != connection && player.sees(position) {
+                outbox.send(player.connection, message.clone());
+            }
+        }
+    }
+
+    fn remove_player(&mut self, connection: u32, keep_session: bool, outbox: &mut MockOutbox) {
+        let index = match self
+            .players
+            .iter()
+            .position(|player| player.connection == connection)
+        {
+            Some(index) => index,
+            None => return,
+        };
+        let player = self.players.remove(index);
+        if !player.joined {
+            return;
+        }
+        self.send_all(MessageData::PlayerDisconnected(player.id), None, outbox);
+        if keep_session && player.time > 0.0 {
+            self.sessions.insert(
+                player.token,
+                Session {
+                    id: player.id,
+                    name: player.name,
+                    time: player.time,
+                    position: player.position,
+                    upgrades: player.upgrades,
+                    expires_at: self.time + RESUME_GRACE,
+                },
+            );
+        }
+    }
+
+    fn kill_player(&mut self, connection: u32, outbox: &mut MockOutbox) {
+        outbox.kick(connection);
+        self.remove_player(connection, false, outbox);
+    }
+
+    fn on_hello(&mut self, connection: u32, hello: MsgHello, outbox: &mut MockOutbox) {
+        if hello.protocol_version != PROTOCOL_VERSION {
+            self.kill_player(connection, outbox);
+            return;
+        }
+        let session = hello
+            .resume_token
+            .and_then(|token| self.sessions.remove(&token));
+        let player = match self.player_mut(connection) {
+            Some(player) if !player.joined => player,
+            _ => return,
+        };
+        if let Some(session) = session {
+            player.id = session.id;
+            player.name = session.name;
+            player.time = session.time;
+            player.position = session.position;
+            player.upgrades = session.upgrades;
+        }
+        player.joined = true;
+        player.init_timer = Some(INIT_DELAY);
+        let message = MessageData::InitPlayer(MsgInitPlayer {
+            info: player.info(),
+            resume_token: player.token.clone(),
+        });
+        outbox.send(connection, message);
+        for pickup in &self.pickups {
+            outbox.send(connection, MessageData::PickupSpawned(pickup.info.clone()));
+        }
+    }
+
+    fn on_player_state(&mut self, connection: u32, state: MsgPlayerState, outbox: &mut MockOutbox) {
+        let player = match self.player_mut(connection) {
+            Some(player) if player.joined => player,
+            _ => return,
+        };
+        player.time = state.time;
+        // once client sends inputs, its position is simulated here.
+        if player.sequence == 0 {
+            player.position = state.position;
+        }
+        if player.time <= 0.0 {
+            self.kill_player(connection, outbox);
+        } else {
+            self.send_nearby(MessageData::PlayerState(state), connection, outbox);
+        }
+    }
+
+    fn on_player_input(&mut self, connection: u32, input: MsgPlayerInput) {
+        let time = self.time;
+        let player_speed = self.player_speed;
+        let rules = self.dash.clone();
+        let player = match self.player_mut(connection) {
+            Some(player) if player.joined => player,
+            _ => return,
+        };
+        if input.sequence <= player.sequence {
+            return;
+        }
+        let delta_time = input.delta_time.clamp(0.0, MAX_INPUT_DELTA);
+        let dashing = input.flags & PLAYER_FLAG_DASH != 0;
+        if !dashing {
+            player.dash = 0.0;
+        }
+        // time itself is reported by client, here we only refuse dashes it cannot afford.
+        if dashing
+            && player.dash <= 0.0
+            && time >= player.dash_ready_at
+            && player.time - rules.cost >= rules.min_time
+        {
+            player.time -= rules.cost;
+            player.dash = rules.duration as f32;
+            player.dash_ready_at = time + rules.cooldown - DASH_COOLDOWN_SLACK;
+        }
+        let multiplier = if dashing && player.dash > 0.0 {
+            rules.multiplier
+        } else {
+            1.0
+        };
+        player.sequence = input.sequence;
+        player.velocity =
+            input.direction * player_speed * player.upgrades.speed_multiplier() * multiplier;
+        player.position = player.position + player.velocity * delta_time;
+        player.dash -= delta_time;
+        player.corrected = false;
+    }
+
+    fn on_collect_pickup(&mut self, connection: u32, id: u32, outbox: &mut MockOutbox) {
+        let index = match self.pickups.iter().position(|pickup| pickup.info.id == id) {
+            Some(index) => index,
+            None => return,
+        };
+        let MsgPickup { time, position, .. } = self.pickups[index].info;
+        let player = match self.player_mut(connection) {
+            Some(player) if player.joined && player.time > 0.0 => player,
+            _ => return,
+        };
+        let reach = PICKUP_REACH + player.upgrades.pickup_radius_bonus();
+        if (player.position - position).sqr_magnitude() > reach * reach {
+            return;
+        }
+        player.time += time;
+        self.pickups.remove(index);
+        outbox.send(connection, MessageData::PlayerEat(time));
+        self.send_all(MessageData::PickupDespawned(id), None, outbox);
+    }
+
+    fn on_purchase_upgrade(
+        &mut self,
+        connection: u32,
+        purchase: MsgPurchaseUpgrade,
+        outbox: &mut MockOutbox,
+    ) {
+        let player = match self.player_mut(connection) {
+            Some(player) if player.joined => player,
+            _ => return,
+        };
+        // level check makes repeated requests harmless.
+        if player.upgrades.next_level(purchase.kind) != Some(purchase.level)
+            || !player.upgrades.can_buy(purchase.kind, player.time)
+        {
+            return;
+        }
+        let cost = Upgrades::cost(purchase.level);
+        player.time -= cost;
+        player.upgrades.set_level(purchase.kind, purchase.level);
+        outbox.send(
+            connection,
+            MessageData::UpgradePurchased(MsgUpgradePurchased {
+                kind: purchase.kind,
+                level: purchase.level,
+                cost,
+            }),
+        );
+    }
+
+    fn process_pickups(&mut self, outbox: &mut MockOutbox) {
+        let time = self.time;
+        let expired = self
+            .pickups
+            .iter()
+            .filter(|pickup| pickup.expires_at <= time)
+            .map(|pickup| pickup.info.id)
+            .collect::<Vec<_>>();
+        self.pickups.retain(|pickup| pickup.expires_at > time);
+        for id in expired {
+            self.send_all(MessageData::PickupDespawned(id), None, outbox);
+        }
+
+        let joined = self
+            .players
+            .iter()
+            .filter(|player| player.joined)
+            .map(|player| player.position)
+            .collect::<Vec<_>>();
+        if joined.is_empty() || self.pickups.len() >= MAX_PICKUPS {
+            return;
+        }
+        let nearby = joined[(self.random() * joined.len() as f64) as usize % joined.len()];
+        let direction = (self.random() * std::f64::consts::PI * 2.0) as Scalar;
+        let distance = self.random() as Scalar * PICKUP_RANGE;
+        let value =
+            PICKUP_TIMES[(self.random() * PICKUP_TIMES.len() as f64) as usize % PICKUP_TIMES.len()];
+        let position = nearby + Vec2::new(direction.cos(), direction.sin()) * distance;
+        self.spawn_pickup(value, position, outbox);
+    }
+
+    fn process_init_timers(&mut self, delta_time: f64, outbox: &mut MockOutbox) {
+        let mut ready = vec![];
+        for player in &mut self.players {
+            if let Some(timer) = &mut player.init_timer {
+                *timer -= delta_time;
+                if *timer <= 0.0 {
+                    player.init_timer = None;
+                    ready.push(player.connection);
+                }
+            }
+        }
+        for connection in ready {
+            let player = match self.players.iter().find(|p| p.connection == connection) {
+                Some(player) => player,
+                None => continue,
+            };
+            for other in &self.players {
+                if other.joined && other.connection != connection && player.sees(other.position) {
+                    outbox.send(connection, MessageData::NewPlayer(other.info()));
+                }
+            }
+            let message = MessageData::NewPlayer(player.info());
+            self.send_nearby(message, connection, outbox);
+        }
+    }
+
+    fn process_collisions(&mut self, outbox: &mut MockOutbox) {
+        let alive = self
+            .players
+            .iter()
+            .filter(|player| player.joined && player.time > 0.0)
+            .map(|player| (player.connection, player.time, player.position))
+            .collect::<Vec<_>>();
+        let mut dead = vec![];
+        for (connection, time, position) in &alive {
+            if dead.contains(connection) {
+                continue;
+            }
+            let other = alive.iter().find(|(other, _, other_position)| {
+                other != connection
+                    && !dead.contains(other)
+                    && Self::players_collide(*position, *other_position)
+            });
+            if let Some((other, other_time, _)) = other {
+                if time > other_time {
+                    outbox.send(*connection, MessageData::PlayerEat(*other_time));
+                    dead.push(*other);
+                } else {
+                    outbox.send(*other, MessageData::PlayerEat(*time));
+                    dead.push(*connection);
+                }
+            }
+        }
+        for connection in dead {
+            self.kill_player(connection, outbox);
+        }
+    }
+}
+
+impl MockServer for LocalServer {
+    fn connect(&mut self, connection: u32, outbox: &mut MockOutbox) -> bool {
+        let position = self.find_init_position();
+        let name = NAMES[(self.random() * NAMES.len() as f64) as usize % NAMES.len()].to_owned();
+        let token = format!("{:016x}", (self.random() * u64::MAX as f64) as u64);
+        let id = self.next_id;
+        self.next_id += 1;
+        self.players.push(Player {
+            connection,
+            id,
+            name,
+            time: INIT_LIFETIME,
+            position,
+            velocity: Vec2::zero(),
+            joined: false,
+            token,
+            sequence: 0,
+            corrected: true,
+            dash: 0.0,
+            dash_ready_at: 0.0,
+            upgrades: Default::default(),
+            init_timer: None,
+            view: None,
+        });
+        outbox.send(
+            connection,
+            MessageData::Welcome(MsgWelcome {
+                protocol_version: PROTOCOL_VERSION,
+                capabilities: 0,
+            }),
+        );
+        true
+    }
+
+    fn disconnect(&mut self, connection: u32, outbox: &mut MockOutbox) {
+        self.remove_player(connection, true, outbox);
+    }
+
+    fn message(&mut self, connection: u32, message: MessageData, outbox: &mut MockOutbox) {
+        match message {
+            MessageData::Hello(hello) => self.on_hello(connection, hello, outbox),
+            MessageData::PlayerState(state) => self.on_player_state(connection, state, outbox),
+            MessageData::PlayerInput(input) => self.on_player_input(connection, input),
+            MessageData::CollectPickup(id) => self.on_collect_pickup(connection, id, outbox),
+            MessageData::PurchaseUpgrade(purchase) => {
+                self.on_purchase_upgrade(connection, purchase, outbox)
+            }
+            MessageData::ViewArea(area) => {
+                if let Some(player) = self.player_mut(connection) {
+                    player.view = Some(area);
+                }
+            }
+            MessageData::Ping(client_time) => outbox.send(
+                connection,
+                MessageData::Pong(MsgPong {
+                    client_time,
+                    server_time: self.time,
+                }),
+            ),
+            _ => {}
+        }
+    }
+
+    fn update(&mut self, delta_time: f64, outbox: &mut MockOutbox) {
+        self.time += delta_time;
+        let time = self.time;
+        self.sessions.retain(|_, session| session.expires_at > time);
+
+        self.process_init_timers(delta_time, outbox);
+
+        self.new_player_timer -= delta_time;
+        if self.new_player_timer <= 0.0 {
+            self.new_player_timer = NEW_PLAYER_INTERVAL;
+            for player in &self.players {
+                if player.joined && player.time > 0.0 {
+                    let message = MessageData::NewPlayer(player.info());
+                    self.send_nearby(message, player.connection, outbox);
+                }
+            }
+        }
+
+        self.collision_timer -= delta_time;
+        if self.collision_timer <= 0.0 {
+            self.collision_timer = COLLISION_INTERVAL;
+            self.process_collisions(outbox);
+        }
+
+        self.pickup_timer -= delta_time;
+        if self.pickup_timer <= 0.0 {
+            self.pickup_timer = PICKUP_INTERVAL;
+            self.process_pickups(outbox);
+        }
+
+        self.correction_timer -= delta_time;
+        if self.correction_timer <= 0.0 {
+            self.correction_timer = CORRECTION_INTERVAL;
+            for player in &mut self.players {
+                if player.joined && !player.corrected {
+                    player.corrected = true;
+                    outbox.send(
+                        player.connection,
+                        MessageData::PlayerCorrection(MsgPlayerCorrection {
+                            sequence: player.sequence,
+                            position: player.position,
+                            velocity: player.velocity,
+                        }),
+                    );
+                }
+            }
+        }
+    }
+}
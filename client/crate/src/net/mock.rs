@@ -0,0 +1,422 @@
+use crate::{
+    consts::PROTOCOL_VERSION,
+    messages::{MessageData, MsgInitPlayer, MsgPlayerInfo, MsgWelcome, MESSAGE_VERSION},
+    net::link::{FrameQueue, LinkConditions, Random},
+};
+use oxygengine::prelude::*;
+use std::{
+    collections::HashMap,
+    convert::TryFrom,
+    ops::Range,
+    sync::{Arc, Mutex},
+};
+
+static HUBS: Mutex<Option<HashMap<String, MockHub>>> = Mutex::new(None);
+
+/// Messages that server wants to send, collected while it handles events.
+#[derive(Default)]
+pub struct MockOutbox {
+    frames: Vec<(u32, MessageID, Vec<u8>)>,
+    kicked: Vec<u32>,
+}
+
+impl MockOutbox {
+    pub fn send(&mut self, connection: u32, message: MessageData) {
+        let id = MessageID::new(message.id(), MESSAGE_VERSION);
+        self.frames.push((connection, id, message.into()));
+    }
+
+    pub fn send_raw(&mut self, connection: u32, id: MessageID, data: Vec<u8>) {
+        self.frames.push((connection, id, data));
+    }
+
+    pub fn kick(&mut self, connection: u32) {
+        self.kicked.push(connection);
+    }
+}
+
+/// Server side of mock network.
+pub trait MockServer: Send {
+    /// Returns false to refuse connection.
+    fn connect(&mut self, _connection: u32, _outbox: &mut MockOutbox) -> bool {
+        true
+    }
+
+    fn disconnect(&mut self, _connection: u32, _outbox: &mut MockOutbox) {}
+
+    fn message(&mut self, connection: u32, message: MessageData, outbox: &mut MockOutbox);
+
+    fn update(&mut self, _delta_time: f64, _outbox: &mut MockOutbox) {}
+}
+
+/// Completes handshake for every client and leaves everything else to test script.
+pub struct ScriptedServer {
+    pub protocol_version: u32,
+    pub time: f32,
+}
+
+impl Default for ScriptedServer {
+    fn default() -> Self {
+        Self {
+            protocol_version: PROTOCOL_VERSION,
+            time: 60.0,
+        }
+    }
+}
+
+impl MockServer for ScriptedServer {
+    fn connect(&mut self, connection: u32, outbox: &mut MockOutbox) -> bool {
+        outbox.send(
+            connection,
+            MessageData::Welcome(MsgWelcome {
+                protocol_version: self.protocol_version,
+                capabilities: 0,
+            }),
+        );
+        true
+    }
+
+    fn message(&mut self, connection: u32, message: MessageData, outbox: &mut MockOutbox) {
+        if let MessageData::Hello(_) = message {
+            outbox.send(
+                connection,
+                MessageData::InitPlayer(MsgInitPlayer {
+                    info: MsgPlayerInfo {
+                        id: connection,
+                        name: format!("Player {}", connection),
+                        time: self.time,
+                        position: Vec2::zero(),
+                    },
+                    resume_token: connection.to_string(),
+                }),
+            );
+        }
+    }
+}
+
+struct Connection {
+    open: bool,
+    to_client: FrameQueue,
+    to_server: FrameQueue,
+    /// Frames lost both ways since client last asked.
+    lost: usize,
+}
+
+struct MockHubInner {
+    server: Box<dyn MockServer>,
+    conditions: LinkConditions,
+    random: Random,
+    time: f64,
+    next_connection: u32,
+    connections: HashMap<u32, Connection>,
+    received: Vec<(u32, MessageData)>,
+}
+
+impl MockHubInner {
+    /// Returns delivery times of frame: none if it gets lost, two if it gets duplicated.
+    fn schedule(&mut self) -> Vec<f64> {
+        self.conditions.schedule(&mut self.random, self.time)
+    }
+
+    fn flush(&mut self, outbox: MockOutbox) {
+        for (connection, id, data) in outbox.frames {
+            let deliveries = self.schedule();
+            if let Some(connection) = self.connections.get_mut(&connection) {
+                if deliveries.is_empty() {
+                    connection.lost += 1;
+                }
+                for deliver_at in deliveries {
+                    connection.to_client.push(deliver_at, id, data.clone());
+                }
+            }
+        }
+        for connection in outbox.kicked {
+            if let Some(connection) = self.connections.get_mut(&connection) {
+                connection.open = false;
+            }
+        }
+    }
+}
+
+/// In-memory network shared by server and all mock clients connected to its URL.
+#[derive(Clone)]
+pub struct MockHub(Arc<Mutex<MockHubInner>>);
+
+impl MockHub {
+    /// Makes `MockClient::open(url)` connect to given server.
+    pub fn register<S>(url: &str, server: S, conditions: LinkConditions) -> Self
+    where
+        S: MockServer + 'static,
+    {
+        let hub = MockHub(Arc::new(Mutex::new(MockHubInner {
+            server: Box::new(server),
+            conditions,
+            random: Random::new(conditions.seed),
+            time: 0.0,
+            next_connection: 1,
+            connections: Default::default(),
+            received: vec![],
+        })));
+        HUBS.lock()
+            .unwrap()
+            .get_or_insert_with(Default::default)
+            .insert(url.to_owned(), hub.clone());
+        hub
+    }
+
+    pub fn unregister(url: &str) {
+        if let Some(hubs) = HUBS.lock().unwrap().as_mut() {
+            hubs.remove(url);
+        }
+    }
+
+    pub fn find(url: &str) -> Option<Self> {
+        HUBS.lock()
+            .unwrap()
+            .as_ref()
+            .and_then(|hubs| hubs.get(url).cloned())
+    }
+
+    pub fn set_conditions(&self, conditions: LinkConditions) {
+        self.0.lock().unwrap().conditions = conditions;
+    }
+
+    pub fn time(&self) -> f64 {
+        self.0.lock().unwrap().time
+    }
+
+    /// Moves time forward, delivers due frames to server and lets it update.
+    pub fn advance(&self, delta_time: f64) {
+        let mut inner = self.0.lock().unwrap();
+        inner.time += delta_time;
+        let time = inner.time;
+        let mut incoming = vec![];
+        loop {
+            // take earliest due frame of all connections.
+            let connection = inner
+                .connections
+                .iter()
+                .filter_map(|(id, connection)| {
+                    connection.to_server.next_due(time).map(|at| (at, *id))
+                })
+                .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap())
+                .map(|(_, id)| id);
+            let frame = match connection {
+                Some(connection) => inner
+                    .connections
+                    .get_mut(&connection)
+                    .and_then(|c| c.to_server.take_due(time))
+                    .map(|frame| (connection, frame)),
+                None => None,
+            };
+            match frame {
+                Some(frame) => incoming.push(frame),
+                None => break,
+            }
+        }
+
+        let mut outbox = MockOutbox::default();
+        for (connection, (id, data)) in incoming {
+            if let Ok(message) = MessageData::try_from((id, data.as_slice())) {
+                inner.received.push((connection, message.clone()));
+                inner.server.message(connection, message, &mut outbox);
+            }
+        }
+        inner.server.update(delta_time, &mut outbox);
+        inner.flush(outbox);
+    }
+
+    /// Sends message from server to client, as if server decided to.
+    pub fn send(&self, connection: u32, message: MessageData) {
+        let mut outbox = MockOutbox::default();
+        outbox.send(connection, message);
+        self.0.lock().unwrap().flush(outbox);
+    }
+
+    pub fn send_raw(&self, connection: u32, id: MessageID, data: Vec<u8>) {
+        let mut outbox = MockOutbox::default();
+        outbox.send_raw(connection, id, data);
+        self.0.lock().unwrap().flush(outbox);
+    }
+
+    /// Drops connection from server side.
+    pub fn kick(&self, connection: u32) {
+        let mut outbox = MockOutbox::default();
+        outbox.kick(connection);
+        self.0.lock().unwrap().flush(outbox);
+    }
+
+    /// Loses connection, so both client and server see it closed.
+    pub fn drop_connection(&self, connection: u32) {
+        self.disconnect(connection);
+    }
+
+    /// Open connections.
+    pub fn connections(&self) -> Vec<u32> {
+        let inner = self.0.lock().unwrap();
+        let mut result = inner
+            .connections
+            .iter()
+            .filter(|(_, connection)| connection.open)
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        result.sort();
+        result
+    }
+
+    /// Messages delivered to server since last call.
+    pub fn take_received(&self) -> Vec<(u32, MessageData)> {
+        std::mem::take(&mut self.0.lock().unwrap().received)
+    }
+
+    fn connect(&self) -> Option<u32> {
+        let mut inner = self.0.lock().unwrap();
+        let connection = inner.next_connection;
+        inner.next_connection += 1;
+        inner.connections.insert(
+            connection,
+            Connection {
+                open: true,
+                to_client: Default::default(),
+                to_server: Default::default(),
+                lost: 0,
+            },
+        );
+        let mut outbox = MockOutbox::default();
+        if inner.server.connect(connection, &mut outbox) {
+            inner.flush(outbox);
+            Some(connection)
+        } else {
+            inner.connections.remove(&connection);
+            None
+        }
+    }
+
+    fn disconnect(&self, connection: u32) {
+        let mut inner = self.0.lock().unwrap();
+        if inner.connections.remove(&connection).is_some() {
+            let mut outbox = MockOutbox::default();
+            inner.server.disconnect(connection, &mut outbox);
+            inner.flush(outbox);
+        }
+    }
+
+    fn is_open(&self, connection: u32) -> bool {
+        self.0
+            .lock()
+            .unwrap()
+            .connections
+            .get(&connection)
+            .map(|connection| connection.open)
+            .unwrap_or(false)
+    }
+
+    fn send_to_server(&self, connection: u32, id: MessageID, data: &[u8]) {
+        let mut inner = self.0.lock().unwrap();
+        let deliveries = inner.schedule();
+        if let Some(connection) = inner.connections.get_mut(&connection) {
+            if connection.open {
+                if deliveries.is_empty() {
+                    connection.lost += 1;
+                }
+                for deliver_at in deliveries {
+                    connection.to_server.push(deliver_at, id, data.to_vec());
+                }
+            }
+        }
+    }
+
+    fn take_lost(&self, connection: u32) -> usize {
+        self.0
+            .lock()
+            .unwrap()
+            .connections
+            .get_mut(&connection)
+            .map(|connection| std::mem::take(&mut connection.lost))
+            .unwrap_or_default()
+    }
+
+    fn read(&self, connection: u32) -> Option<(MessageID, Vec<u8>)> {
+        let mut inner = self.0.lock().unwrap();
+        let time = inner.time;
+        inner
+            .connections
+            .get_mut(&connection)?
+            .to_client
+            .take_due(time)
+    }
+}
+
+/// Client connected to `MockHub` registered for its URL.
+pub struct MockClient {
+    id: ClientID,
+    hub: MockHub,
+    connection: u32,
+    closed: bool,
+}
+
+impl MockClient {
+    pub fn connection(&self) -> u32 {
+        self.connection
+    }
+
+    /// Number of frames lost both ways since last call.
+    pub fn take_lost(&mut self) -> usize {
+        self.hub.take_lost(self.connection)
+    }
+
+    /// Takes all frames delivered so far.
+    pub fn read_all(&mut self) -> Vec<(MessageID, Vec<u8>)> {
+        let mut result = vec![];
+        while let Some(frame) = self.receive() {
+            result.push(frame);
+        }
+        result
+    }
+}
+
+impl Client for MockClient {
+    fn open(url: &str) -> Option<Self> {
+        let hub = MockHub::find(url)?;
+        let connection = hub.connect()?;
+        Some(Self {
+            id: ClientID::new(),
+            hub,
+            connection,
+            closed: false,
+        })
+    }
+
+    fn close(mut self) -> Self {
+        self.hub.disconnect(self.connection);
+        self.closed = true;
+        self
+    }
+
+    fn id(&self) -> ClientID {
+        self.id
+    }
+
+    fn state(&self) -> ClientState {
+        if !self.closed && self.hub.is_open(self.connection) {
+            ClientState::Open
+        } else {
+            ClientState::Closed
+        }
+    }
+
+    fn send(&mut self, id: MessageID, data: &[u8]) -> Option<Range<usize>> {
+        if self.closed {
+            return None;
+        }
+        self.hub.send_to_server(self.connection, id, data);
+        Some(0..data.len())
+    }
+
+    fn receive(&mut self) -> Option<(MessageID, Vec<u8>)> {
+        if self.closed {
+            return None;
+        }
+        self.hub.read(self.connection)
+    }
+}
@@ -0,0 +1,86 @@
+pub mod compact;
+pub mod link;
+pub mod local_server;
+pub mod mock;
+pub mod simulator;
+
+use oxygengine::prelude::*;
+use std::ops::Range;
+
+/// Network client used by game states, goes through `NetworkSimulator` registered for its URL.
+pub type GameClient = simulator::SimulatedClient<DirectClient>;
+
+/// Connects to `MockHub` registered for given URL (like local server used for offline play),
+/// otherwise (in browser) to web socket server.
+pub enum DirectClient {
+    Mock(mock::MockClient),
+    #[cfg(target_arch = "wasm32")]
+    Web(WebClient),
+}
+
+impl DirectClient {
+    /// Number of frames lost both ways since last call, as far as client can tell.
+    pub fn take_lost(&mut self) -> usize {
+        match self {
+            DirectClient::Mock(client) => client.take_lost(),
+            #[cfg(target_arch = "wasm32")]
+            DirectClient::Web(_) => 0,
+        }
+    }
+}
+
+impl Client for DirectClient {
+    fn open(url: &str) -> Option<Self> {
+        if let Some(client) = mock::MockClient::open(url) {
+            return Some(DirectClient::Mock(client));
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            WebClient::open(url).map(DirectClient::Web)
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            None
+        }
+    }
+
+    fn close(self) -> Self {
+        match self {
+            DirectClient::Mock(client) => DirectClient::Mock(client.close()),
+            #[cfg(target_arch = "wasm32")]
+            DirectClient::Web(client) => DirectClient::Web(client.close()),
+        }
+    }
+
+    fn id(&self) -> ClientID {
+        match self {
+            DirectClient::Mock(client) => client.id(),
+            #[cfg(target_arch = "wasm32")]
+            DirectClient::Web(client) => client.id(),
+        }
+    }
+
+    fn state(&self) -> ClientState {
+        match self {
+            DirectClient::Mock(client) => client.state(),
+            #[cfg(target_arch = "wasm32")]
+            DirectClient::Web(client) => client.state(),
+        }
+    }
+
+    fn send(&mut self, id: MessageID, data: &[u8]) -> Option<Range<usize>> {
+        match self {
+            DirectClient::Mock(client) => client.send(id, data),
+            #[cfg(target_arch = "wasm32")]
+            DirectClient::Web(client) => client.send(id, data),
+        }
+    }
+
+    fn receive(&mut self) -> Option<(MessageID, Vec<u8>)> {
+        match self {
+            DirectClient::Mock(client) => client.receive(),
+            #[cfg(target_arch = "wasm32")]
+            DirectClient::Web(client) => client.receive(),
+        }
+    }
+}
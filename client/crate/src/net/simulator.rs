@@ -0,0 +1,228 @@
+use crate::net::link::{FrameQueue, LinkConditions, Random};
+use oxygengine::prelude::*;
+use std::{
+    collections::HashMap,
+    ops::Range,
+    sync::{Arc, Mutex},
+};
+
+static SIMULATORS: Mutex<Option<HashMap<String, NetworkSimulator>>> = Mutex::new(None);
+
+struct NetworkSimulatorInner {
+    conditions: LinkConditions,
+    enabled: bool,
+    time: f64,
+}
+
+/// Conditions and clock shared by all `SimulatedClient`s opened to its URL.
+#[derive(Clone)]
+pub struct NetworkSimulator(Arc<Mutex<NetworkSimulatorInner>>);
+
+impl NetworkSimulator {
+    /// Makes clients opened to given URL go through simulator (enabled by default).
+    pub fn register(url: &str, conditions: LinkConditions) -> Self {
+        let simulator = NetworkSimulator(Arc::new(Mutex::new(NetworkSimulatorInner {
+            conditions,
+            enabled: true,
+            time: 0.0,
+        })));
+        SIMULATORS
+            .lock()
+            .unwrap()
+            .get_or_insert_with(Default::default)
+            .insert(url.to_owned(), simulator.clone());
+        simulator
+    }
+
+    pub fn unregister(url: &str) {
+        if let Some(simulators) = SIMULATORS.lock().unwrap().as_mut() {
+            simulators.remove(url);
+        }
+    }
+
+    pub fn find(url: &str) -> Option<Self> {
+        SIMULATORS
+            .lock()
+            .unwrap()
+            .as_ref()
+            .and_then(|simulators| simulators.get(url).cloned())
+    }
+
+    /// Moves time of all registered simulators forward.
+    pub fn advance_all(delta_time: f64) {
+        if let Some(simulators) = SIMULATORS.lock().unwrap().as_ref() {
+            for simulator in simulators.values() {
+                simulator.advance(delta_time);
+            }
+        }
+    }
+
+    pub fn conditions(&self) -> LinkConditions {
+        self.0.lock().unwrap().conditions
+    }
+
+    pub fn set_conditions(&self, conditions: LinkConditions) {
+        self.0.lock().unwrap().conditions = conditions;
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.0.lock().unwrap().enabled
+    }
+
+    /// Disabled simulator lets new frames through right away.
+    pub fn set_enabled(&self, enabled: bool) {
+        self.0.lock().unwrap().enabled = enabled;
+    }
+
+    pub fn time(&self) -> f64 {
+        self.0.lock().unwrap().time
+    }
+
+    pub fn advance(&self, delta_time: f64) {
+        self.0.lock().unwrap().time += delta_time;
+    }
+}
+
+/// Wraps any client and delays, drops, duplicates or reorders its frames according to
+/// `NetworkSimulator` registered for its URL. Without one it only passes frames through.
+pub struct SimulatedClient<C>
+where
+    C: Client,
+{
+    client: C,
+    simulator: Option<NetworkSimulator>,
+    random: Random,
+    outgoing: FrameQueue,
+    incoming: FrameQueue,
+    lost: usize,
+}
+
+impl<C> SimulatedClient<C>
+where
+    C: Client,
+{
+    pub fn client(&self) -> &C {
+        &self.client
+    }
+
+    pub fn client_mut(&mut self) -> &mut C {
+        &mut self.client
+    }
+
+    /// Number of frames simulator lost both ways since last call.
+    pub fn take_lost(&mut self) -> usize {
+        std::mem::take(&mut self.lost)
+    }
+
+    /// Takes all frames that are due.
+    pub fn read_all(&mut self) -> Vec<(MessageID, Vec<u8>)> {
+        let mut result = vec![];
+        while let Some(frame) = self.receive() {
+            result.push(frame);
+        }
+        result
+    }
+
+    /// Sends outgoing frames that are due, without waiting for next `receive`.
+    pub fn process(&mut self) {
+        if let Some(simulator) = self.simulator.clone() {
+            self.flush(simulator.time());
+        }
+    }
+
+    /// Returns delivery times of frame: none if it gets lost, two if it gets duplicated.
+    fn schedule(&mut self, simulator: &NetworkSimulator) -> Vec<f64> {
+        let (conditions, enabled, time) = {
+            let inner = simulator.0.lock().unwrap();
+            (inner.conditions, inner.enabled, inner.time)
+        };
+        if !enabled {
+            return vec![time];
+        }
+        conditions.schedule(&mut self.random, time)
+    }
+
+    fn push(&mut self, simulator: &NetworkSimulator, outgoing: bool, id: MessageID, data: Vec<u8>) {
+        let deliveries = self.schedule(simulator);
+        if deliveries.is_empty() {
+            self.lost += 1;
+        }
+        let queue = if outgoing {
+            &mut self.outgoing
+        } else {
+            &mut self.incoming
+        };
+        for deliver_at in deliveries {
+            queue.push(deliver_at, id, data.clone());
+        }
+    }
+
+    /// Sends outgoing frames that are due to wrapped client.
+    fn flush(&mut self, time: f64) {
+        while let Some((id, data)) = self.outgoing.take_due(time) {
+            self.client.send(id, &data);
+        }
+    }
+}
+
+impl<C> Client for SimulatedClient<C>
+where
+    C: Client,
+{
+    fn open(url: &str) -> Option<Self> {
+        let simulator = NetworkSimulator::find(url);
+        let random = Random::new(
+            simulator
+                .as_ref()
+                .map(|simulator| simulator.conditions().seed)
+                .unwrap_or(1),
+        );
+        C::open(url).map(|client| Self {
+            client,
+            simulator,
+            random,
+            outgoing: Default::default(),
+            incoming: Default::default(),
+            lost: 0,
+        })
+    }
+
+    fn close(self) -> Self {
+        Self {
+            client: self.client.close(),
+            outgoing: Default::default(),
+            incoming: Default::default(),
+            ..self
+        }
+    }
+
+    fn id(&self) -> ClientID {
+        self.client.id()
+    }
+
+    fn state(&self) -> ClientState {
+        self.client.state()
+    }
+
+    fn send(&mut self, id: MessageID, data: &[u8]) -> Option<Range<usize>> {
+        let simulator = match self.simulator.clone() {
+            Some(simulator) => simulator,
+            None => return self.client.send(id, data),
+        };
+        self.push(&simulator, true, id, data.to_vec());
+        self.flush(simulator.time());
+        Some(0..data.len())
+    }
+
+    fn receive(&mut self) -> Option<(MessageID, Vec<u8>)> {
+        let simulator = match self.simulator.clone() {
+            Some(simulator) => simulator,
+            None => return self.client.receive(),
+        };
+        self.flush(simulator.time());
+        while let Some((id, data)) = self.client.receive() {
+            self.push(&simulator, false, id, data);
+        }
+        self.incoming.take_due(simulator.time())
+    }
+}
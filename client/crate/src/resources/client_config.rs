@@ -0,0 +1,252 @@
+use crate::{
+    components::{
+        dash::Dash,
+        send_policy::{AdaptiveSendPolicy, FixedSendPolicy, StateSendPolicy},
+        time_decay::{DecayCurve, TimeDecay},
+    },
+    consts::{
+        CAMERA_SCALE, DASH_COOLDOWN, DASH_COST, DASH_DURATION, DASH_MULTIPLIER, HOST_URL,
+        IDLE_SEND_STATE_DELAY, MIN_DASH_TIME, MIN_SEND_STATE_DELAY, PLAYER_SPEED, SEND_STATE_DELAY,
+        SIMULATED_DUPLICATE, SIMULATED_JITTER, SIMULATED_LATENCY, SIMULATED_LOSS,
+        SIMULATED_REORDER, STATE_BANDWIDTH, TIME_DECAY_ACCELERATING, TIME_DECAY_MOVING,
+        TIME_DECAY_PROPORTIONAL, TIME_DECAY_RATE,
+    },
+    macros::console_log,
+    net::link::LinkConditions,
+};
+use oxygengine::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Runtime settings, taken from (in order of priority): URL query parameters,
+/// `config.json` asset and compiled defaults.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClientConfig {
+    pub host_url: String,
+    pub send_state_delay: f64,
+    /// `adaptive` or `fixed` (every `send_state_delay`).
+    pub send_policy: String,
+    pub idle_send_state_delay: f64,
+    /// Bytes per second that player states may take.
+    pub state_bandwidth: f64,
+    pub player_speed: Scalar,
+    /// Seconds of time spent on every dash.
+    pub dash_cost: Scalar,
+    pub dash_duration: f64,
+    pub dash_multiplier: Scalar,
+    pub dash_cooldown: f64,
+    /// Dash is refused if it would leave less time than this.
+    pub min_dash_time: Scalar,
+    /// `constant`, `proportional` (to time left), `accelerating` (over match) or `moving`.
+    pub time_decay: String,
+    /// Seconds lost per second with constant decay.
+    pub time_decay_rate: Scalar,
+    /// Extra decay per second of time left with proportional decay.
+    pub time_decay_proportional: Scalar,
+    /// Extra decay per second played with accelerating decay.
+    pub time_decay_accelerating: Scalar,
+    /// Seconds lost per second while moving with moving decay.
+    pub time_decay_moving: Scalar,
+    pub camera_scale: Scalar,
+    /// Passes traffic through network simulator with conditions below.
+    pub simulate_network: bool,
+    pub simulated_latency: f64,
+    pub simulated_jitter: f64,
+    pub simulated_loss: f64,
+    pub simulated_duplicate: f64,
+    pub simulated_reorder: f64,
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        Self {
+            host_url: HOST_URL.to_owned(),
+            send_state_delay: SEND_STATE_DELAY,
+            send_policy: "adaptive".to_owned(),
+            idle_send_state_delay: IDLE_SEND_STATE_DELAY,
+            state_bandwidth: STATE_BANDWIDTH,
+            player_speed: PLAYER_SPEED,
+            dash_cost: DASH_COST,
+            dash_duration: DASH_DURATION,
+            dash_multiplier: DASH_MULTIPLIER,
+            dash_cooldown: DASH_COOLDOWN,
+            min_dash_time: MIN_DASH_TIME,
+            time_decay: "constant".to_owned(),
+            time_decay_rate: TIME_DECAY_RATE,
+            time_decay_proportional: TIME_DECAY_PROPORTIONAL,
+            time_decay_accelerating: TIME_DECAY_ACCELERATING,
+            time_decay_moving: TIME_DECAY_MOVING,
+            camera_scale: CAMERA_SCALE,
+            simulate_network: false,
+            simulated_latency: SIMULATED_LATENCY,
+            simulated_jitter: SIMULATED_JITTER,
+            simulated_loss: SIMULATED_LOSS,
+            simulated_duplicate: SIMULATED_DUPLICATE,
+            simulated_reorder: SIMULATED_REORDER,
+        }
+    }
+}
+
+impl ClientConfig {
+    /// Names of all settings, as accepted by `set` and read from URL query parameters.
+    pub const SETTINGS: &'static [&'static str] = &[
+        "host_url",
+        "send_state_delay",
+        "send_policy",
+        "idle_send_state_delay",
+        "state_bandwidth",
+        "player_speed",
+        "dash_cost",
+        "dash_duration",
+        "dash_multiplier",
+        "dash_cooldown",
+        "min_dash_time",
+        "time_decay",
+        "time_decay_rate",
+        "time_decay_proportional",
+        "time_decay_accelerating",
+        "time_decay_moving",
+        "camera_scale",
+        "simulate_network",
+        "simulated_latency",
+        "simulated_jitter",
+        "simulated_loss",
+        "simulated_duplicate",
+        "simulated_reorder",
+    ];
+
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Overrides setting by its field name; returns false if name or value is invalid.
+    pub fn set(&mut self, name: &str, value: &str) -> bool {
+        match name {
+            "host_url" => {
+                self.host_url = value.to_owned();
+                true
+            }
+            "send_state_delay" => parse_into(value, &mut self.send_state_delay),
+            "send_policy" => {
+                self.send_policy = value.to_owned();
+                true
+            }
+            "idle_send_state_delay" => parse_into(value, &mut self.idle_send_state_delay),
+            "state_bandwidth" => parse_into(value, &mut self.state_bandwidth),
+            "player_speed" => parse_into(value, &mut self.player_speed),
+            "dash_cost" => parse_into(value, &mut self.dash_cost),
+            "dash_duration" => parse_into(value, &mut self.dash_duration),
+            "dash_multiplier" => parse_into(value, &mut self.dash_multiplier),
+            "dash_cooldown" => parse_into(value, &mut self.dash_cooldown),
+            "min_dash_time" => parse_into(value, &mut self.min_dash_time),
+            "time_decay" => {
+                if self.decay_curve(value).is_none() {
+                    return false;
+                }
+                self.time_decay = value.to_owned();
+                true
+            }
+            "time_decay_rate" => parse_into(value, &mut self.time_decay_rate),
+            "time_decay_proportional" => parse_into(value, &mut self.time_decay_proportional),
+            "time_decay_accelerating" => parse_into(value, &mut self.time_decay_accelerating),
+            "time_decay_moving" => parse_into(value, &mut self.time_decay_moving),
+            "camera_scale" => parse_into(value, &mut self.camera_scale),
+            "simulate_network" => parse_into(value, &mut self.simulate_network),
+            "simulated_latency" => parse_into(value, &mut self.simulated_latency),
+            "simulated_jitter" => parse_into(value, &mut self.simulated_jitter),
+            "simulated_loss" => parse_into(value, &mut self.simulated_loss),
+            "simulated_duplicate" => parse_into(value, &mut self.simulated_duplicate),
+            "simulated_reorder" => parse_into(value, &mut self.simulated_reorder),
+            _ => false,
+        }
+    }
+
+    pub fn state_send_policy(&self) -> StateSendPolicy {
+        match self.send_policy.as_str() {
+            "fixed" => StateSendPolicy::new(FixedSendPolicy::new(self.send_state_delay)),
+            _ => StateSendPolicy::new(AdaptiveSendPolicy::new(
+                MIN_SEND_STATE_DELAY,
+                self.send_state_delay,
+                self.idle_send_state_delay,
+                self.state_bandwidth,
+            )),
+        }
+    }
+
+    pub fn dash(&self) -> Dash {
+        Dash::new(
+            self.dash_cost,
+            self.dash_duration,
+            self.dash_multiplier,
+            self.dash_cooldown,
+            self.min_dash_time,
+        )
+    }
+
+    pub fn time_decay(&self) -> TimeDecay {
+        TimeDecay::new(self.decay_curve(&self.time_decay).unwrap_or_else(|| {
+            console_log!(
+                "* Unknown time decay curve `{}`, using constant",
+                self.time_decay
+            );
+            DecayCurve::Constant(self.time_decay_rate)
+        }))
+    }
+
+    fn decay_curve(&self, name: &str) -> Option<DecayCurve> {
+        match name {
+            "constant" => Some(DecayCurve::Constant(self.time_decay_rate)),
+            "proportional" => Some(DecayCurve::Proportional(self.time_decay_proportional)),
+            "accelerating" => Some(DecayCurve::Accelerating(self.time_decay_accelerating)),
+            "moving" => Some(DecayCurve::Moving(self.time_decay_moving)),
+            _ => None,
+        }
+    }
+
+    pub fn link_conditions(&self) -> LinkConditions {
+        LinkConditions {
+            latency: self.simulated_latency,
+            jitter: self.simulated_jitter,
+            loss: self.simulated_loss,
+            duplicate: self.simulated_duplicate,
+            reorder: self.simulated_reorder,
+            ..Default::default()
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn apply_query_params(&mut self) {
+        let search = match web_sys::window().and_then(|window| window.location().search().ok()) {
+            Some(search) => search,
+            None => return,
+        };
+        let params = match web_sys::UrlSearchParams::new_with_str(&search) {
+            Ok(params) => params,
+            Err(_) => return,
+        };
+        for name in Self::SETTINGS {
+            if let Some(value) = params.get(name) {
+                if !self.set(name, &value) {
+                    console_log!("* Invalid query parameter `{}`: {}", name, value);
+                }
+            }
+        }
+    }
+
+    /// There are no query parameters outside of the browser.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn apply_query_params(&mut self) {}
+}
+
+fn parse_into<T>(value: &str, target: &mut T) -> bool
+where
+    T: std::str::FromStr,
+{
+    match value.parse() {
+        Ok(value) => {
+            *target = value;
+            true
+        }
+        Err(_) => false,
+    }
+}
@@ -0,0 +1,30 @@
+use oxygengine::prelude::*;
+
+/// Player and enemy that overlap in current frame.
+#[derive(Debug, Copy, Clone)]
+pub struct CollisionEvent {
+    pub player: Entity,
+    pub enemy: Entity,
+    /// How deep colliders overlap.
+    pub depth: Scalar,
+}
+
+/// Collisions found by `CollisionSystem`, replaced every frame.
+#[derive(Debug, Default, Clone)]
+pub struct Collisions {
+    events: Vec<CollisionEvent>,
+}
+
+impl Collisions {
+    pub fn events(&self) -> &[CollisionEvent] {
+        &self.events
+    }
+
+    pub fn clear(&mut self) {
+        self.events.clear();
+    }
+
+    pub fn push(&mut self, event: CollisionEvent) {
+        self.events.push(event);
+    }
+}
@@ -0,0 +1,5 @@
+pub mod client_config;
+pub mod collisions;
+pub mod network_clock;
+pub mod network_stats;
+pub mod shop;
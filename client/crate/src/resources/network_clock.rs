@@ -0,0 +1,59 @@
+/// Weight of the newest sample in smoothed values.
+const SMOOTHING: f64 = 0.1;
+
+/// Estimates round-trip time, its jitter and offset between local and server clock
+/// from ping/pong exchanges.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct NetworkClock {
+    local_time: f64,
+    rtt: f64,
+    jitter: f64,
+    offset: f64,
+    samples: usize,
+}
+
+impl NetworkClock {
+    pub fn advance(&mut self, delta_time: f64) {
+        self.local_time += delta_time;
+    }
+
+    pub fn local_time(&self) -> f64 {
+        self.local_time
+    }
+
+    pub fn server_time(&self) -> f64 {
+        self.local_time + self.offset
+    }
+
+    pub fn rtt(&self) -> f64 {
+        self.rtt
+    }
+
+    pub fn jitter(&self) -> f64 {
+        self.jitter
+    }
+
+    pub fn offset(&self) -> f64 {
+        self.offset
+    }
+
+    pub fn is_synchronized(&self) -> bool {
+        self.samples > 0
+    }
+
+    pub fn on_pong(&mut self, client_time: f64, server_time: f64) {
+        let rtt = (self.local_time - client_time).max(0.0);
+        // server sampled its time halfway through the round trip.
+        let offset = server_time + rtt * 0.5 - self.local_time;
+        if self.samples == 0 {
+            self.rtt = rtt;
+            self.jitter = 0.0;
+            self.offset = offset;
+        } else {
+            self.jitter += ((rtt - self.rtt).abs() - self.jitter) * SMOOTHING;
+            self.rtt += (rtt - self.rtt) * SMOOTHING;
+            self.offset += (offset - self.offset) * SMOOTHING;
+        }
+        self.samples += 1;
+    }
+}
@@ -0,0 +1,95 @@
+/// Length of window that per second rates are measured over.
+const WINDOW: f64 = 1.0;
+
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct Traffic {
+    pub packets: usize,
+    pub bytes: usize,
+}
+
+impl Traffic {
+    fn add(&mut self, size: usize) {
+        self.packets += 1;
+        self.bytes += size;
+    }
+}
+
+/// Connection quality counters updated by game messaging and shown by stats overlay.
+#[derive(Debug, Default, Clone)]
+pub struct NetworkStats {
+    /// Whether stats overlay is shown.
+    pub visible: bool,
+    /// Number of enemies tracked by game.
+    pub enemies: usize,
+    elapsed: f64,
+    incoming: Traffic,
+    outgoing: Traffic,
+    incoming_rate: Traffic,
+    outgoing_rate: Traffic,
+    malformed: usize,
+    dropped: usize,
+}
+
+impl NetworkStats {
+    pub fn advance(&mut self, delta_time: f64) {
+        self.elapsed += delta_time;
+        if self.elapsed >= WINDOW {
+            let scale = 1.0 / self.elapsed;
+            self.incoming_rate = scale_traffic(self.incoming, scale);
+            self.outgoing_rate = scale_traffic(self.outgoing, scale);
+            self.incoming = Default::default();
+            self.outgoing = Default::default();
+            self.elapsed = 0.0;
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    /// Called for every received frame of given size (in bytes).
+    pub fn received(&mut self, size: usize) {
+        self.incoming.add(size);
+    }
+
+    /// Called for every sent frame of given size (in bytes).
+    pub fn sent(&mut self, size: usize) {
+        self.outgoing.add(size);
+    }
+
+    /// Called for every received frame that could not be decoded.
+    pub fn malformed(&mut self) {
+        self.malformed += 1;
+    }
+
+    /// Called for frames lost on the way (as far as client can tell) and decoded messages that
+    /// could not be used, e.g. state of unknown player.
+    pub fn dropped(&mut self, count: usize) {
+        self.dropped += count;
+    }
+
+    /// Packets and bytes received per second, measured over last full window.
+    pub fn incoming_rate(&self) -> Traffic {
+        self.incoming_rate
+    }
+
+    /// Packets and bytes sent per second, measured over last full window.
+    pub fn outgoing_rate(&self) -> Traffic {
+        self.outgoing_rate
+    }
+
+    pub fn malformed_count(&self) -> usize {
+        self.malformed
+    }
+
+    pub fn dropped_count(&self) -> usize {
+        self.dropped
+    }
+}
+
+fn scale_traffic(traffic: Traffic, scale: f64) -> Traffic {
+    Traffic {
+        packets: (traffic.packets as f64 * scale).round() as usize,
+        bytes: (traffic.bytes as f64 * scale).round() as usize,
+    }
+}
@@ -0,0 +1,26 @@
+use crate::messages::UpgradeKind;
+
+/// Upgrade shop panel state and purchases player asked for, sent to server by game.
+#[derive(Debug, Default, Clone)]
+pub struct Shop {
+    /// Whether shop panel is shown.
+    pub visible: bool,
+    requests: Vec<UpgradeKind>,
+}
+
+impl Shop {
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    /// Asks to buy next level of upgrade, ignored while shop is closed.
+    pub fn request(&mut self, kind: UpgradeKind) {
+        if self.visible && !self.requests.contains(&kind) {
+            self.requests.push(kind);
+        }
+    }
+
+    pub fn drain_requests(&mut self) -> Vec<UpgradeKind> {
+        std::mem::take(&mut self.requests)
+    }
+}
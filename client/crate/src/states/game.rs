@@ -1,20 +1,44 @@
 use crate::{
     components::{
+        collider::Collider,
+        dash::Dash,
         enemy_controller::EnemyController,
         follow::{Follow, FollowMode},
         owned_by::OwnedBy,
+        pickup::Pickup,
         player_controller::PlayerController,
+        send_policy::{SendSample, StateSendPolicy},
+        snapshot_buffer::SnapshotBuffer,
         speed::Speed,
         time::Time,
+        upgrades::Upgrades,
         velocity::Velocity,
         EnemyTag, PlayerTag, TimerTag,
     },
-    consts::SEND_STATE_DELAY,
-    messages::{MessageData, MsgPlayerInfo, MsgPlayerState},
+    consts::{
+        CAPABILITY_COMPACT_STATE, CLIENT_CAPABILITIES, INPUT_SEND_DELAY, INTERPOLATION_DELAY,
+        MAX_EXTRAPOLATION, MAX_UPGRADE_LEVEL, PICKUP_RADIUS, PING_DELAY, PLAYER_RADIUS, PREY_ALPHA,
+        PROTOCOL_VERSION, RECONNECT_BASE_DELAY, RECONNECT_GRACE, RECONNECT_MAX_DELAY,
+        VIEW_AREA_DELAY, VIEW_AREA_MARGIN, VIEW_AREA_SCALE, VIEW_AREA_TOLERANCE,
+    },
+    macros::console_log,
+    messages::{
+        MessageData, MsgHello, MsgInitPlayer, MsgPickup, MsgPlayerInfo, MsgPlayerState,
+        MsgPurchaseUpgrade, MsgUpgradePurchased, MsgViewArea, UpgradeKind, HEADER_SIZE,
+        PLAYER_FLAG_DASH,
+    },
+    net::{compact::StateEncoder, GameClient},
+    resources::{
+        client_config::ClientConfig, collisions::Collisions, network_clock::NetworkClock,
+        network_stats::NetworkStats, shop::Shop,
+    },
     states::lobby::LobbyState,
 };
 use oxygengine::prelude::*;
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    convert::TryFrom,
+};
 
 #[rustfmt::skip]
 const WATER_MASK_ALPHA_MATRIX: &[Scalar] = &[
@@ -27,31 +51,110 @@ const WATER_MASK_ALPHA_MATRIX: &[Scalar] = &[
     0.05, 0.05, 0.05, 0.05, 0.05, 0.05, 0.05,
 ];
 
+struct Reconnect {
+    client: Option<ClientID>,
+    attempt: u32,
+    /// Time left to next connection attempt.
+    delay: f64,
+    /// Time since connection was lost.
+    elapsed: f64,
+    welcomed: bool,
+    capabilities: u32,
+    overlay: Entity,
+}
+
 pub struct GameState {
     client: ClientID,
     info: MsgPlayerInfo,
+    resume_token: String,
+    /// capabilities supported by both client and server.
+    capabilities: u32,
+    state_encoder: StateEncoder,
+    reconnect: Option<Reconnect>,
     entities: HashSet<Entity>,
     water: Option<Entity>,
     camera: Option<Entity>,
     player: Option<Entity>,
     enemies: HashMap<u32, Entity>,
-    send_state_timer: f64,
+    pickups: HashMap<u32, Entity>,
+    ping_timer: f64,
+    input_timer: f64,
+    /// Last view area sent to server.
+    view_area: Option<MsgViewArea>,
+    view_area_timer: f64,
+    /// Lines of network stats overlay, empty when hidden.
+    stats_overlay: Vec<Entity>,
+    /// Lines of upgrade shop panel, empty when hidden.
+    shop_overlay: Vec<Entity>,
 }
 
 impl GameState {
-    pub fn new(id: ClientID, info: MsgPlayerInfo) -> Self {
+    pub fn new(id: ClientID, init: MsgInitPlayer, capabilities: u32) -> Self {
         Self {
             client: id,
-            info,
+            info: init.info,
+            resume_token: init.resume_token,
+            capabilities,
+            state_encoder: Default::default(),
+            reconnect: None,
             entities: Default::default(),
             water: None,
             camera: None,
             player: None,
             enemies: Default::default(),
-            send_state_timer: 0.0,
+            pickups: Default::default(),
+            ping_timer: 0.0,
+            input_timer: 0.0,
+            view_area: None,
+            view_area_timer: 0.0,
+            stats_overlay: vec![],
+            shop_overlay: vec![],
         }
     }
 
+    /// Sends message to server, returns size of sent frame (in bytes).
+    fn send(&self, message: MessageData, world: &mut World) -> usize {
+        let id = message.id();
+        let data: Vec<u8> = message.into();
+        world
+            .write_resource::<Network<GameClient>>()
+            .send(self.client, id, &data);
+        let size = HEADER_SIZE + data.len();
+        world.write_resource::<NetworkStats>().sent(size);
+        size
+    }
+
+    /// Decodes and unpacks frames received by client, skipping (and counting) malformed ones.
+    /// Also counts frames that got lost on the way.
+    fn read_messages(
+        network: &mut Network<GameClient>,
+        client: ClientID,
+        stats: &mut NetworkStats,
+    ) -> Vec<MessageData> {
+        if let Some(client) = network.client_mut(client) {
+            stats.dropped(client.take_lost() + client.client_mut().take_lost());
+        }
+        network
+            .read(client)
+            .map(|messages| {
+                messages
+                    .filter_map(|msg| {
+                        stats.received(HEADER_SIZE + msg.1.len());
+                        match MessageData::try_from(msg) {
+                            Ok(msg) => Some(msg),
+                            Err(error) => {
+                                console_log!("* Skipped malformed message: {}", error);
+                                stats.malformed();
+                                None
+                            }
+                        }
+                    })
+                    .flat_map(MessageData::unpack)
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default()
+    }
+
     fn create_water(&mut self, world: &mut World) {
         let mut commands = vec![Command::Store];
         for col in -3..=3 {
@@ -82,6 +185,15 @@ impl GameState {
     }
 
     fn create_player(&mut self, world: &mut World) {
+        let (speed, dash, decay, send_policy) = {
+            let config = world.read_resource::<ClientConfig>();
+            (
+                config.player_speed,
+                config.dash(),
+                config.time_decay(),
+                config.state_send_policy(),
+            )
+        };
         let player = world
             .create_entity()
             .with(CompositeRenderable(
@@ -96,10 +208,15 @@ impl GameState {
             .with(CompositeRenderDepth(1.0))
             .with(CompositeTransform::translation(self.info.position))
             .with(PlayerController::new(self.info.id))
-            .with(Speed(400.0))
+            .with(Speed(speed))
+            .with(dash)
+            .with(Upgrades::default())
             .with(Velocity(0.0.into()))
+            .with(Collider(PLAYER_RADIUS))
+            .with(send_policy)
             .with(PlayerTag)
             .with(Time(self.info.time))
+            .with(decay)
             .with(Tag("default".into()))
             .build();
         self.player = Some(player);
@@ -152,7 +269,15 @@ impl GameState {
         if info.time <= 0.0 || info.id == self.info.id || self.enemies.contains_key(&info.id) {
             return;
         }
+        if let Some(area) = &self.view_area {
+            if !area.contains(info.position, VIEW_AREA_MARGIN) {
+                return;
+            }
+        }
 
+        let mut buffer = SnapshotBuffer::new(INTERPOLATION_DELAY, MAX_EXTRAPOLATION);
+        let server_time = world.read_resource::<NetworkClock>().server_time();
+        buffer.push(server_time, info.position, 0.0.into());
         let enemy = world
             .create_entity()
             .with(CompositeRenderable(
@@ -166,7 +291,10 @@ impl GameState {
             ))
             .with(CompositeTransform::translation(info.position))
             .with(EnemyController::new(info.id))
+            .with(buffer)
             .with(Velocity(0.0.into()))
+            .with(Collider(PLAYER_RADIUS))
+            .with(Dash::default())
             .with(EnemyTag)
             .with(Time(info.time))
             .with(Tag("default".into()))
@@ -227,9 +355,101 @@ impl GameState {
             if let Some(velocity) = world.write_storage::<Velocity>().get_mut(*entity) {
                 velocity.0 = state.velocity;
             }
-            if let Some(transform) = world.write_storage::<CompositeTransform>().get_mut(*entity) {
-                transform.set_translation(state.position);
+            if let Some(dash) = world.write_storage::<Dash>().get_mut(*entity) {
+                dash.set_active(state.flags & PLAYER_FLAG_DASH != 0);
+            }
+            if let Some(buffer) = world.write_storage::<SnapshotBuffer>().get_mut(*entity) {
+                buffer.push(state.timestamp, state.position, state.velocity);
             }
+        } else {
+            // state of player that was not announced (or already left view area).
+            world.write_resource::<NetworkStats>().dropped(1);
+        }
+    }
+
+    /// Forgets enemies that left view area, server will announce them again when they are back.
+    fn destroy_hidden_enemies(&mut self, world: &mut World) {
+        let area = match &self.view_area {
+            Some(area) => area,
+            None => return,
+        };
+        let hidden = {
+            let transforms = world.read_storage::<CompositeTransform>();
+            self.enemies
+                .iter()
+                .filter(|(_, entity)| {
+                    transforms
+                        .get(**entity)
+                        .map(|t| !area.contains(t.get_translation(), VIEW_AREA_MARGIN))
+                        .unwrap_or(false)
+                })
+                .map(|(id, _)| *id)
+                .collect::<Vec<_>>()
+        };
+        for id in hidden {
+            self.destroy_enemy(id, world);
+        }
+    }
+
+    fn create_pickup(&mut self, info: MsgPickup, world: &mut World) {
+        if self.pickups.contains_key(&info.id) {
+            return;
+        }
+
+        let size = PICKUP_RADIUS * 2.0;
+        let pickup = world
+            .create_entity()
+            .with(CompositeRenderable(
+                Image {
+                    image: "pickup.png".into(),
+                    source: None,
+                    destination: Some([0.0, 0.0, size, size].into()),
+                    alignment: 0.5.into(),
+                }
+                .into(),
+            ))
+            .with(CompositeRenderDepth(0.5))
+            .with(CompositeTransform::translation(info.position))
+            .with(Pickup::new(info.id, info.time))
+            .with(Collider(PICKUP_RADIUS))
+            .with(Tag("default".into()))
+            .build();
+        self.entities.insert(pickup);
+        self.pickups.insert(info.id, pickup);
+    }
+
+    fn destroy_pickup(&mut self, id: u32, world: &mut World) {
+        if let Some(entity) = self.pickups.remove(&id) {
+            self.entities.remove(&entity);
+            drop(world.delete_entity(entity));
+        }
+    }
+
+    /// Fades enemies that player overlaps and is older than. Only server decides (with its own
+    /// collision radius) who gets eaten, so they are kept until it says so.
+    fn process_collisions(&mut self, world: &mut World) {
+        let prey = {
+            let collisions = world.read_resource::<Collisions>();
+            let times = world.read_storage::<Time>();
+            collisions
+                .events()
+                .iter()
+                .filter(|event| {
+                    let player = times.get(event.player).map(|t| t.0).unwrap_or_default();
+                    let enemy = times.get(event.enemy).map(|t| t.0).unwrap_or_default();
+                    player > enemy
+                })
+                .map(|event| event.enemy)
+                .collect::<HashSet<_>>()
+        };
+        let mut alphas = world.write_storage::<CompositeRenderAlpha>();
+        for entity in self.enemies.values() {
+            let alpha = if prey.contains(entity) {
+                PREY_ALPHA
+            } else {
+                1.0
+            };
+            drop(alphas.insert(*entity, CompositeRenderAlpha(alpha)));
         }
     }
 
@@ -239,20 +459,369 @@ impl GameState {
             drop(world.delete_entity(entity));
         }
     }
+
+    fn stats_lines(&self, world: &World) -> Vec<String> {
+        let clock = world.read_resource::<NetworkClock>();
+        let stats = world.read_resource::<NetworkStats>();
+        let incoming = stats.incoming_rate();
+        let outgoing = stats.outgoing_rate();
+        vec![
+            format!(
+                "RTT: {:.0} ms (jitter {:.0} ms)",
+                clock.rtt() * 1000.0,
+                clock.jitter() * 1000.0
+            ),
+            format!("In: {} packets/s, {} B/s", incoming.packets, incoming.bytes),
+            format!(
+                "Out: {} packets/s, {} B/s",
+                outgoing.packets, outgoing.bytes
+            ),
+            format!(
+                "Dropped: {}, malformed: {}",
+                stats.dropped_count(),
+                stats.malformed_count()
+            ),
+            format!("Enemies: {}", self.enemies.len()),
+            format!(
+                "Interpolation delay: {:.0} ms",
+                INTERPOLATION_DELAY * 1000.0
+            ),
+        ]
+    }
+
+    /// Shows, hides or refreshes network stats overlay.
+    fn update_stats_overlay(&mut self, world: &mut World) {
+        let lines = if world.read_resource::<NetworkStats>().visible {
+            Some(self.stats_lines(world))
+        } else {
+            None
+        };
+        Self::update_overlay(
+            &mut self.entities,
+            &mut self.stats_overlay,
+            lines,
+            240.0,
+            world,
+        );
+    }
+
+    fn shop_lines(&self, world: &World) -> Vec<String> {
+        let (upgrades, time) = match self.player {
+            Some(player) => (
+                world
+                    .read_storage::<Upgrades>()
+                    .get(player)
+                    .cloned()
+                    .unwrap_or_default(),
+                world
+                    .read_storage::<Time>()
+                    .get(player)
+                    .map(|t| t.0)
+                    .unwrap_or_default(),
+            ),
+            None => Default::default(),
+        };
+        let mut lines = vec![format!("Shop (B to close), time left: {:.0} s", time)];
+        for (index, kind) in UpgradeKind::ALL.iter().enumerate() {
+            let level = upgrades.level(*kind);
+            let price = match upgrades.next_level(*kind) {
+                Some(next) => format!("next costs {:.0} s", Upgrades::cost(next)),
+                None => "max".to_owned(),
+            };
+            lines.push(format!(
+                "[{}] {}: level {}/{}, {}",
+                index + 1,
+                kind.name(),
+                level,
+                MAX_UPGRADE_LEVEL,
+                price
+            ));
+        }
+        lines
+    }
+
+    /// Shows, hides or refreshes upgrade shop panel.
+    fn update_shop_overlay(&mut self, world: &mut World) {
+        let lines = if world.read_resource::<Shop>().visible {
+            Some(self.shop_lines(world))
+        } else {
+            None
+        };
+        Self::update_overlay(
+            &mut self.entities,
+            &mut self.shop_overlay,
+            lines,
+            420.0,
+            world,
+        );
+    }
+
+    /// Shows text lines on `ui` camera starting at given height, removes them for `None`.
+    fn update_overlay(
+        entities: &mut HashSet<Entity>,
+        overlay: &mut Vec<Entity>,
+        lines: Option<Vec<String>>,
+        top: Scalar,
+        world: &mut World,
+    ) {
+        let lines = match lines {
+            Some(lines) => lines,
+            None => {
+                for entity in overlay.drain(..) {
+                    entities.remove(&entity);
+                    drop(world.delete_entity(entity));
+                }
+                return;
+            }
+        };
+
+        if overlay.is_empty() {
+            for (index, line) in lines.into_iter().enumerate() {
+                let entity = world
+                    .create_entity()
+                    .with(CompositeRenderable(
+                        Text {
+                            color: Color::white(),
+                            font: "Verdana".into(),
+                            align: TextAlign::Left,
+                            text: line.into(),
+                            position: 0.0.into(),
+                            size: 20.0,
+                        }
+                        .into(),
+                    ))
+                    .with(CompositeRenderDepth(2.0))
+                    .with(CompositeTransform::translation(
+                        [30.0, top + 26.0 * index as Scalar].into(),
+                    ))
+                    .with(Tag("ui".into()))
+                    .build();
+                entities.insert(entity);
+                overlay.push(entity);
+            }
+        } else {
+            let mut renderables = world.write_storage::<CompositeRenderable>();
+            for (entity, line) in overlay.iter().zip(lines) {
+                if let Some(renderable) = renderables.get_mut(*entity) {
+                    if let Renderable::Text(text) = &mut renderable.0 {
+                        text.text = line.into();
+                    }
+                }
+            }
+        }
+    }
+
+    /// Turns upgrades player picked in shop into purchase requests it can afford.
+    fn purchase_requests(&self, world: &mut World) -> Vec<MsgPurchaseUpgrade> {
+        let kinds = world.write_resource::<Shop>().drain_requests();
+        let player = match self.player {
+            Some(player) => player,
+            None => return vec![],
+        };
+        let upgrades = world
+            .read_storage::<Upgrades>()
+            .get(player)
+            .cloned()
+            .unwrap_or_default();
+        let time = world
+            .read_storage::<Time>()
+            .get(player)
+            .map(|t| t.0)
+            .unwrap_or_default();
+        kinds
+            .into_iter()
+            .filter(|kind| upgrades.can_buy(*kind, time))
+            .filter_map(|kind| {
+                upgrades
+                    .next_level(kind)
+                    .map(|level| MsgPurchaseUpgrade { kind, level })
+            })
+            .collect()
+    }
+
+    /// Applies upgrade confirmed by server and takes its price from player time.
+    fn apply_upgrade(&mut self, purchase: MsgUpgradePurchased, world: &mut World) {
+        let player = match self.player {
+            Some(player) => player,
+            None => return,
+        };
+        let mut upgrades = world.write_storage::<Upgrades>();
+        let upgrades = match upgrades.get_mut(player) {
+            Some(upgrades) => upgrades,
+            None => return,
+        };
+        // confirmation of level player already has is a duplicate.
+        if upgrades.next_level(purchase.kind) != Some(purchase.level) {
+            return;
+        }
+        upgrades.set_level(purchase.kind, purchase.level);
+        if let Some(time) = world.write_storage::<Time>().get_mut(player) {
+            time.0 -= purchase.cost;
+        }
+    }
+
+    fn process_reconnect(&mut self, world: &mut World) -> StateChange {
+        let delta_time = world.read_resource::<AppLifeCycle>().delta_time_seconds();
+        if self.reconnect.is_none() {
+            console_log!("* Connection lost, reconnecting...");
+            let overlay = world
+                .create_entity()
+                .with(CompositeRenderable(
+                    Text {
+                        color: Color::white(),
+                        font: "Verdana".into(),
+                        align: TextAlign::Left,
+                        text: "Reconnecting...".into(),
+                        position: 0.0.into(),
+                        size: 48.0,
+                    }
+                    .into(),
+                ))
+                .with(CompositeRenderDepth(2.0))
+                .with(CompositeTransform::translation([30.0, 160.0].into()))
+                .with(Tag("ui".into()))
+                .build();
+            self.entities.insert(overlay);
+            self.reconnect = Some(Reconnect {
+                client: None,
+                attempt: 0,
+                delay: 0.0,
+                elapsed: 0.0,
+                welcomed: false,
+                capabilities: 0,
+                overlay,
+            });
+        }
+
+        // server gets fresh player state after resume so pending inputs are useless.
+        if let Some(player) = self.player {
+            if let Some(controller) = world.write_storage::<PlayerController>().get_mut(player) {
+                controller.drain_outbox();
+            }
+        }
+
+        let host_url = world.read_resource::<ClientConfig>().host_url.clone();
+        let mut network = world.write_resource::<Network<GameClient>>();
+        let reconnect = self.reconnect.as_mut().unwrap();
+        reconnect.elapsed += delta_time;
+        if reconnect.elapsed > RECONNECT_GRACE {
+            if let Some(client) = reconnect.client {
+                network.close_client(client);
+            }
+            return StateChange::Swap(Box::new(LobbyState::default()));
+        }
+        if let Some(client) = reconnect.client {
+            if !network.has_client(client) {
+                reconnect.client = None;
+            }
+        }
+
+        let client = match reconnect.client {
+            Some(client) => client,
+            None => {
+                reconnect.delay -= delta_time;
+                if reconnect.delay <= 0.0 {
+                    reconnect.client = network.open_client(&host_url);
+                    reconnect.welcomed = false;
+                    let backoff = RECONNECT_BASE_DELAY * 2.0_f64.powi(reconnect.attempt as i32);
+                    reconnect.delay = backoff.min(RECONNECT_MAX_DELAY);
+                    reconnect.attempt += 1;
+                }
+                return StateChange::None;
+            }
+        };
+
+        let messages = Self::read_messages(
+            &mut network,
+            client,
+            &mut world.write_resource::<NetworkStats>(),
+        );
+        for msg in messages {
+            match msg {
+                MessageData::Welcome(welcome) => {
+                    if welcome.protocol_version != PROTOCOL_VERSION {
+                        network.close_client(client);
+                        return StateChange::Swap(Box::new(LobbyState::default()));
+                    }
+                    reconnect.welcomed = true;
+                    reconnect.capabilities = welcome.capabilities & CLIENT_CAPABILITIES;
+                    let message =
+                        MessageData::Hello(MsgHello::new(Some(self.resume_token.clone())));
+                    let id = message.id();
+                    let data: Vec<u8> = message.into();
+                    network.send(client, id, &data);
+                }
+                MessageData::InitPlayer(init) if reconnect.welcomed => {
+                    let capabilities = reconnect.capabilities;
+                    drop(network);
+                    return self.resume(client, init, capabilities, world);
+                }
+                _ => {}
+            }
+        }
+        StateChange::None
+    }
+
+    fn resume(
+        &mut self,
+        client: ClientID,
+        init: MsgInitPlayer,
+        capabilities: u32,
+        world: &mut World,
+    ) -> StateChange {
+        if init.info.id != self.info.id {
+            // session has expired on server so we were given a brand new player.
+            return StateChange::Swap(Box::new(GameState::new(client, init, capabilities)));
+        }
+
+        console_log!("* Connection resumed");
+        if let Some(reconnect) = self.reconnect.take() {
+            self.entities.remove(&reconnect.overlay);
+            drop(world.delete_entity(reconnect.overlay));
+        }
+        // we might have missed disconnections, server will announce everyone again soon.
+        let enemies = self.enemies.keys().cloned().collect::<Vec<_>>();
+        for id in enemies {
+            self.destroy_enemy(id, world);
+        }
+        // server sends all pickups again after hello.
+        let pickups = self.pickups.keys().cloned().collect::<Vec<_>>();
+        for id in pickups {
+            self.destroy_pickup(id, world);
+        }
+        self.client = client;
+        self.resume_token = init.resume_token;
+        self.capabilities = capabilities;
+        // new connection knows none of previously sent states.
+        self.state_encoder = Default::default();
+        if let Some(player) = self.player {
+            if let Some(policy) = world.write_storage::<StateSendPolicy>().get_mut(player) {
+                policy.0.reset();
+            }
+        }
+        self.ping_timer = 0.0;
+        self.input_timer = 0.0;
+        self.view_area = None;
+        self.view_area_timer = 0.0;
+        StateChange::None
+    }
 }
 
 impl State for GameState {
     fn on_enter(&mut self, world: &mut World) {
+        world.register::<StateSendPolicy>();
+        world.register::<EnemyController>();
         self.create_player(world);
         self.create_water(world);
 
         {
+            let scale = world.read_resource::<ClientConfig>().camera_scale;
             let mut camera = CompositeCamera::new(CompositeScalingMode::CenterAspect);
             camera.tags = vec!["default".into()];
             let camera = world
                 .create_entity()
                 .with(camera)
-                .with(CompositeTransform::scale(1024.0.into()))
+                .with(CompositeTransform::scale(scale.into()))
                 .with(Follow::new(self.player.unwrap()))
                 .build();
             self.camera = Some(camera);
@@ -289,24 +858,22 @@ impl State for GameState {
     }
 
     fn on_process(&mut self, world: &mut World) -> StateChange {
-        let network = world.read_resource::<Network<WebClient>>();
-        if !network.has_client(self.client) {
-            return StateChange::Swap(Box::new(LobbyState::default()));
-        }
-
         for entity in world.read_resource::<HierarchyChangeRes>().removed() {
             self.entities.remove(entity);
         }
 
+        let mut network = world.write_resource::<Network<GameClient>>();
+        if self.reconnect.is_some() || !network.has_client(self.client) {
+            drop(network);
+            return self.process_reconnect(world);
+        }
+
         // process messages.
-        let messages = network
-            .read(self.client)
-            .map(|messages| {
-                messages
-                    .map(|msg| MessageData::from(msg))
-                    .collect::<Vec<_>>()
-            })
-            .unwrap_or_default();
+        let messages = Self::read_messages(
+            &mut network,
+            self.client,
+            &mut world.write_resource::<NetworkStats>(),
+        );
         drop(network);
         for msg in messages {
             match msg {
@@ -316,11 +883,42 @@ impl State for GameState {
                 MessageData::PlayerState(state) => {
                     self.update_enemy(state, world);
                 }
-                MessageData::PlayerDisconnected(id) => {
-                    if id != self.info.id {
-                        self.destroy_enemy(id, world);
+                MessageData::PlayerStateCompact(state) => match state.resolve(None) {
+                    Ok(state) => self.update_enemy(state.to_state(), world),
+                    Err(error) => {
+                        console_log!("* Skipped player state: {}", error);
+                        world.write_resource::<NetworkStats>().dropped(1);
+                    }
+                },
+                MessageData::PlayerStateAck(sequence) => {
+                    self.state_encoder.acknowledge(sequence);
+                }
+                MessageData::PlayerDisconnected(id) if id != self.info.id => {
+                    self.destroy_enemy(id, world);
+                }
+                MessageData::PickupSpawned(info) => {
+                    self.create_pickup(info, world);
+                }
+                MessageData::PickupDespawned(id) => {
+                    self.destroy_pickup(id, world);
+                }
+                MessageData::PlayerCorrection(correction) => {
+                    if let Some(player) = self.player {
+                        if let Some(controller) =
+                            world.write_storage::<PlayerController>().get_mut(player)
+                        {
+                            controller.correct(correction);
+                        }
                     }
                 }
+                MessageData::Pong(pong) => {
+                    world
+                        .write_resource::<NetworkClock>()
+                        .on_pong(pong.client_time, pong.server_time);
+                }
+                MessageData::UpgradePurchased(purchase) => {
+                    self.apply_upgrade(purchase, world);
+                }
                 MessageData::PlayerEat(t) => {
                     if let Some(player) = self.player {
                         if let Some(time) = world.write_storage::<Time>().get_mut(player) {
@@ -332,6 +930,8 @@ impl State for GameState {
             };
         }
 
+        self.process_collisions(world);
+
         // check time out.
         if let Some(player) = self.player {
             let time = world
@@ -341,17 +941,104 @@ impl State for GameState {
                 .unwrap_or_default();
             if time <= 0.0 {
                 world
-                    .write_resource::<Network<WebClient>>()
+                    .write_resource::<Network<GameClient>>()
                     .close_client(self.client);
                 return StateChange::Swap(Box::new(LobbyState::default()));
             }
         }
 
-        // send player state.
-        self.send_state_timer -= world.read_resource::<AppLifeCycle>().delta_time_seconds();
-        if self.send_state_timer <= 0.0 {
-            self.send_state_timer = SEND_STATE_DELAY;
+        // send ping.
+        let delta_time = world.read_resource::<AppLifeCycle>().delta_time_seconds();
+        self.ping_timer -= delta_time;
+        if self.ping_timer <= 0.0 {
+            self.ping_timer = PING_DELAY;
+            let time = world.read_resource::<NetworkClock>().local_time();
+            self.send(MessageData::Ping(time), world);
+        }
+
+        // send view area.
+        self.view_area_timer -= delta_time;
+        if self.view_area_timer <= 0.0 {
+            self.view_area_timer = VIEW_AREA_DELAY;
+            if let Some(player) = self.player {
+                let center = world
+                    .read_storage::<CompositeTransform>()
+                    .get(player)
+                    .map(|t| t.get_translation())
+                    .unwrap_or_default();
+                let scale = world.read_resource::<ClientConfig>().camera_scale * VIEW_AREA_SCALE;
+                let area = MsgViewArea {
+                    center,
+                    half_extents: Vec2::new(scale, scale),
+                };
+                let changed = match &self.view_area {
+                    Some(sent) => {
+                        (sent.center - area.center).magnitude() > VIEW_AREA_TOLERANCE
+                            || sent.half_extents != area.half_extents
+                    }
+                    None => true,
+                };
+                if changed {
+                    self.send(MessageData::ViewArea(area.clone()), world);
+                    self.view_area = Some(area);
+                }
+            }
+            self.destroy_hidden_enemies(world);
+        }
+
+        // send player inputs recorded since last send.
+        self.input_timer -= delta_time;
+        if self.input_timer <= 0.0 {
+            self.input_timer = INPUT_SEND_DELAY;
             if let Some(player) = self.player {
+                let inputs = world
+                    .write_storage::<PlayerController>()
+                    .get_mut(player)
+                    .map(|controller| controller.drain_outbox())
+                    .unwrap_or_default();
+                for input in inputs {
+                    self.send(MessageData::PlayerInput(input), world);
+                }
+            }
+        }
+
+        // ask server for touched pickups.
+        let requests = (&mut world.write_storage::<Pickup>())
+            .join()
+            .filter_map(|pickup| {
+                if pickup.take_request() {
+                    Some(pickup.id)
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+        for id in requests {
+            self.send(MessageData::CollectPickup(id), world);
+        }
+
+        // ask server for upgrades picked in shop.
+        for purchase in self.purchase_requests(world) {
+            self.send(MessageData::PurchaseUpgrade(purchase), world);
+        }
+
+        // send player state when its policy says so.
+        if let Some(player) = self.player {
+            let velocity = world
+                .read_storage::<Velocity>()
+                .get(player)
+                .map(|v| v.0)
+                .unwrap_or_default();
+            let sample = SendSample {
+                delta_time,
+                velocity,
+            };
+            let send = world
+                .write_storage::<StateSendPolicy>()
+                .get_mut(player)
+                .map(|policy| policy.0.update(&sample))
+                .unwrap_or(false);
+            if send {
                 let time = world
                     .read_storage::<Time>()
                     .get(player)
@@ -362,25 +1049,36 @@ impl State for GameState {
                     .get(player)
                     .map(|t| t.get_translation())
                     .unwrap_or_default();
-                let velocity = world
-                    .read_storage::<Velocity>()
+                let flags = world
+                    .read_storage::<Dash>()
                     .get(player)
-                    .map(|v| v.0)
+                    .map(|dash| dash.flags())
                     .unwrap_or_default();
-                let message = MessageData::PlayerState(MsgPlayerState {
+                let timestamp = world.read_resource::<NetworkClock>().server_time();
+                let state = MsgPlayerState {
                     id: self.info.id,
                     time,
                     position,
                     velocity,
-                });
-                let id = message.id();
-                let data: Vec<u8> = message.into();
-                world
-                    .write_resource::<Network<WebClient>>()
-                    .send(self.client, id, &data);
+                    timestamp,
+                    flags,
+                };
+                let message = if self.capabilities & CAPABILITY_COMPACT_STATE != 0 {
+                    MessageData::PlayerStateCompact(self.state_encoder.encode(&state))
+                } else {
+                    MessageData::PlayerState(state)
+                };
+                let size = self.send(message, world);
+                if let Some(policy) = world.write_storage::<StateSendPolicy>().get_mut(player) {
+                    policy.0.sent(size);
+                }
             }
         }
 
+        world.write_resource::<NetworkStats>().enemies = self.enemies.len();
+        self.update_stats_overlay(world);
+        self.update_shop_overlay(world);
+
         StateChange::None
     }
 }
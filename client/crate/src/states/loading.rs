@@ -1,4 +1,14 @@
-use crate::states::lobby::LobbyState;
+use crate::{
+    macros::console_log,
+    net::{
+        link::LinkConditions,
+        local_server::{LocalServer, LOCAL_SERVER_URL},
+        mock::MockHub,
+        simulator::NetworkSimulator,
+    },
+    resources::client_config::ClientConfig,
+    states::lobby::LobbyState,
+};
 use oxygengine::prelude::*;
 use std::collections::HashSet;
 
@@ -46,11 +56,31 @@ impl State for LoadingState {
     }
 
     fn on_process(&mut self, world: &mut World) -> StateChange {
-        let assets = &world.read_resource::<AssetsDatabase>();
-        if assets.is_ready() {
-            StateChange::Swap(Box::new(LobbyState::default()))
-        } else {
-            StateChange::None
+        let assets = world.read_resource::<AssetsDatabase>();
+        if !assets.is_ready() {
+            return StateChange::None;
         }
+
+        let mut config = assets
+            .asset_by_path("text://config.json")
+            .and_then(|asset| asset.get::<TextAsset>())
+            .map(|asset| {
+                ClientConfig::from_json(asset.get()).unwrap_or_else(|error| {
+                    console_log!("* Invalid `config.json`: {}", error);
+                    ClientConfig::default()
+                })
+            })
+            .unwrap_or_default();
+        config.apply_query_params();
+        drop(assets);
+        MockHub::register(
+            LOCAL_SERVER_URL,
+            LocalServer::with_config(1, &config),
+            LinkConditions::default(),
+        );
+        NetworkSimulator::register(&config.host_url, config.link_conditions())
+            .set_enabled(config.simulate_network);
+        *world.write_resource::<ClientConfig>() = config;
+        StateChange::Swap(Box::new(LobbyState::default()))
     }
 }
@@ -1,13 +1,43 @@
-use crate::{consts::HOST_URL, messages::MessageData, states::game::GameState};
+use crate::{
+    consts::{CLIENT_CAPABILITIES, PROTOCOL_VERSION},
+    macros::console_log,
+    messages::{MessageData, MsgHello},
+    net::GameClient,
+    resources::client_config::ClientConfig,
+    states::game::GameState,
+};
 use oxygengine::prelude::*;
-use std::collections::HashSet;
+use std::{collections::HashSet, convert::TryFrom};
 
 #[derive(Default)]
 pub struct LobbyState {
     client: Option<ClientID>,
+    welcomed: bool,
+    /// capabilities supported by both client and server.
+    capabilities: u32,
+    /// server protocol version if it differs from client one.
+    mismatch: Option<u32>,
+    label: Option<Entity>,
     entities: HashSet<Entity>,
 }
 
+impl LobbyState {
+    fn show_mismatch(&mut self, server_version: u32, world: &mut World) {
+        let text = if server_version > PROTOCOL_VERSION {
+            "Client outdated! Please refresh."
+        } else {
+            "Server outdated! Try again later."
+        };
+        if let Some(label) = self.label {
+            if let Some(renderable) = world.write_storage::<CompositeRenderable>().get_mut(label) {
+                if let Renderable::Text(label) = &mut renderable.0 {
+                    label.text = text.into();
+                }
+            }
+        }
+    }
+}
+
 impl State for LobbyState {
     fn on_enter(&mut self, world: &mut World) {
         let camera = world
@@ -32,6 +62,7 @@ impl State for LobbyState {
             ))
             .with(CompositeTransform::translation([0.0, 300.0].into()))
             .build();
+        self.label = Some(label);
         self.entities.insert(label);
     }
 
@@ -42,28 +73,77 @@ impl State for LobbyState {
     }
 
     fn on_process(&mut self, world: &mut World) -> StateChange {
-        let network = &mut world.write_resource::<Network<WebClient>>();
+        if self.mismatch.is_some() {
+            return StateChange::None;
+        }
+
+        let host_url = world.read_resource::<ClientConfig>().host_url.clone();
+        let mut network = world.write_resource::<Network<GameClient>>();
         if self.client.is_none() {
-            self.client = network.open_client(HOST_URL);
+            self.client = network.open_client(&host_url);
+            self.welcomed = false;
         }
-        if let Some(client) = self.client {
-            if network.has_client(client) {
-                drop(if let Some(messages) = network.read(client) {
-                    for msg in messages {
-                        let msg = MessageData::from(msg);
-                        match msg {
-                            MessageData::InitPlayer(info) => {
-                                self.client = None;
-                                return StateChange::Swap(Box::new(GameState::new(client, info)));
-                            }
-                            _ => {}
+        let client = match self.client {
+            Some(client) => client,
+            None => return StateChange::None,
+        };
+        if !network.has_client(client) {
+            self.client = None;
+            return StateChange::None;
+        }
+
+        let messages = network
+            .read(client)
+            .map(|messages| {
+                messages
+                    .filter_map(|msg| match MessageData::try_from(msg) {
+                        Ok(msg) => Some(msg),
+                        Err(error) => {
+                            console_log!("* Skipped malformed message: {}", error);
+                            None
                         }
+                    })
+                    .flat_map(MessageData::unpack)
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        for msg in messages {
+            match msg {
+                MessageData::Welcome(welcome) => {
+                    if welcome.protocol_version != PROTOCOL_VERSION {
+                        self.mismatch = Some(welcome.protocol_version);
+                        break;
+                    }
+                    self.welcomed = true;
+                    self.capabilities = welcome.capabilities & CLIENT_CAPABILITIES;
+                    let message = MessageData::Hello(MsgHello::new(None));
+                    let id = message.id();
+                    let data: Vec<u8> = message.into();
+                    network.send(client, id, &data);
+                }
+                MessageData::InitPlayer(init) => {
+                    // server that skips handshake is older than any versioned one.
+                    if !self.welcomed {
+                        self.mismatch = Some(0);
+                        break;
                     }
-                });
-            } else {
-                self.client = None;
+                    self.client = None;
+                    return StateChange::Swap(Box::new(GameState::new(
+                        client,
+                        init,
+                        self.capabilities,
+                    )));
+                }
+                _ => {}
             }
         }
+
+        if let Some(server_version) = self.mismatch {
+            network.close_client(client);
+            self.client = None;
+            drop(network);
+            self.show_mismatch(server_version, world);
+        }
         StateChange::None
     }
 }
@@ -0,0 +1,114 @@
+use crate::{
+    components::{collider::Collider, EnemyTag, PlayerTag},
+    consts::COLLISION_CELL_SIZE,
+    resources::collisions::{CollisionEvent, Collisions},
+};
+use oxygengine::prelude::*;
+use std::collections::HashMap;
+
+struct Circle<T> {
+    item: T,
+    position: Vec2,
+    radius: Scalar,
+}
+
+/// Uniform grid of circles, each stored in cell containing its center.
+pub struct SpatialHash<T> {
+    cell_size: Scalar,
+    /// Biggest inserted radius, tells how many neighbour cells query has to look at.
+    max_radius: Scalar,
+    cells: HashMap<(i32, i32), Vec<Circle<T>>>,
+}
+
+impl<T> SpatialHash<T>
+where
+    T: Copy,
+{
+    pub fn new(cell_size: Scalar) -> Self {
+        Self {
+            cell_size,
+            max_radius: 0.0,
+            cells: Default::default(),
+        }
+    }
+
+    pub fn insert(&mut self, item: T, position: Vec2, radius: Scalar) {
+        self.max_radius = self.max_radius.max(radius);
+        self.cells
+            .entry(self.cell(position))
+            .or_default()
+            .push(Circle {
+                item,
+                position,
+                radius,
+            });
+    }
+
+    /// Returns items overlapping given circle with depth of overlap.
+    pub fn query(&self, position: Vec2, radius: Scalar) -> Vec<(T, Scalar)> {
+        let reach = Vec2::from(radius + self.max_radius);
+        let (min_col, min_row) = self.cell(position - reach);
+        let (max_col, max_row) = self.cell(position + reach);
+        let mut result = vec![];
+        for col in min_col..=max_col {
+            for row in min_row..=max_row {
+                if let Some(circles) = self.cells.get(&(col, row)) {
+                    for circle in circles {
+                        let distance = (circle.position - position).magnitude();
+                        let depth = radius + circle.radius - distance;
+                        if depth >= 0.0 {
+                            result.push((circle.item, depth));
+                        }
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    fn cell(&self, position: Vec2) -> (i32, i32) {
+        (
+            (position.x / self.cell_size).floor() as i32,
+            (position.y / self.cell_size).floor() as i32,
+        )
+    }
+}
+
+/// Finds players overlapping enemies, so game can react before server decides.
+pub struct CollisionSystem;
+
+impl<'s> System<'s> for CollisionSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, Collider>,
+        ReadStorage<'s, CompositeTransform>,
+        ReadStorage<'s, PlayerTag>,
+        ReadStorage<'s, EnemyTag>,
+        Write<'s, Collisions>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, colliders, transforms, player_tag, enemy_tag, mut collisions): Self::SystemData,
+    ) {
+        collisions.clear();
+        let mut hash = SpatialHash::new(COLLISION_CELL_SIZE);
+        for (entity, collider, transform, _) in
+            (&entities, &colliders, &transforms, &enemy_tag).join()
+        {
+            hash.insert(entity, transform.get_translation(), collider.0);
+        }
+
+        for (player, collider, transform, _) in
+            (&entities, &colliders, &transforms, &player_tag).join()
+        {
+            for (enemy, depth) in hash.query(transform.get_translation(), collider.0) {
+                collisions.push(CollisionEvent {
+                    player,
+                    enemy,
+                    depth,
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use crate::{components::dash::Dash, consts::DASH_SCALE};
+use oxygengine::prelude::*;
+
+/// Counts down dashes and stretches players (local and remote) while they dash.
+pub struct DashSystem;
+
+impl<'s> System<'s> for DashSystem {
+    type SystemData = (
+        ReadExpect<'s, AppLifeCycle>,
+        WriteStorage<'s, Dash>,
+        WriteStorage<'s, CompositeTransform>,
+    );
+
+    fn run(&mut self, (lifecycle, mut dashes, mut transforms): Self::SystemData) {
+        let dt = lifecycle.delta_time_seconds();
+
+        for (dash, transform) in (&mut dashes, &mut transforms).join() {
+            dash.update(dt);
+            let scale = if dash.is_active() { DASH_SCALE } else { 1.0 };
+            transform.set_scale(scale.into());
+        }
+    }
+}
@@ -0,0 +1,24 @@
+use crate::{components::snapshot_buffer::SnapshotBuffer, resources::network_clock::NetworkClock};
+use oxygengine::prelude::*;
+
+pub struct InterpolationSystem;
+
+impl<'s> System<'s> for InterpolationSystem {
+    type SystemData = (
+        ReadExpect<'s, NetworkClock>,
+        WriteStorage<'s, SnapshotBuffer>,
+        WriteStorage<'s, CompositeTransform>,
+    );
+
+    fn run(&mut self, (clock, mut buffers, mut transforms): Self::SystemData) {
+        let server_time = clock.server_time();
+
+        for (buffer, transform) in (&mut buffers, &mut transforms).join() {
+            let render_time = server_time - buffer.delay;
+            buffer.prune(render_time);
+            if let Some(position) = buffer.sample(render_time) {
+                transform.set_translation(position);
+            }
+        }
+    }
+}
@@ -0,0 +1,15 @@
+use crate::net::{local_server::LOCAL_SERVER_URL, mock::MockHub};
+use oxygengine::prelude::*;
+
+/// Runs local server used for offline play.
+pub struct LocalServerSystem;
+
+impl<'s> System<'s> for LocalServerSystem {
+    type SystemData = ReadExpect<'s, AppLifeCycle>;
+
+    fn run(&mut self, lifecycle: Self::SystemData) {
+        if let Some(hub) = MockHub::find(LOCAL_SERVER_URL) {
+            hub.advance(lifecycle.delta_time_seconds());
+        }
+    }
+}
@@ -1,4 +1,13 @@
-pub mod enemy_control;
+pub mod collision;
+pub mod dash;
 pub mod follow;
+pub mod interpolation;
+pub mod local_server;
+pub mod network_clock;
+pub mod network_simulator;
+pub mod network_stats;
+pub mod pickup;
 pub mod player_control;
+pub mod reconciliation;
+pub mod shop;
 pub mod time;
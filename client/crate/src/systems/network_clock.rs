@@ -0,0 +1,12 @@
+use crate::resources::network_clock::NetworkClock;
+use oxygengine::prelude::*;
+
+pub struct NetworkClockSystem;
+
+impl<'s> System<'s> for NetworkClockSystem {
+    type SystemData = (ReadExpect<'s, AppLifeCycle>, WriteExpect<'s, NetworkClock>);
+
+    fn run(&mut self, (lifecycle, mut clock): Self::SystemData) {
+        clock.advance(lifecycle.delta_time_seconds());
+    }
+}
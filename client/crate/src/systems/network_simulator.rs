@@ -0,0 +1,25 @@
+use crate::{
+    macros::console_log, net::simulator::NetworkSimulator, resources::client_config::ClientConfig,
+};
+use oxygengine::prelude::*;
+
+pub struct NetworkSimulatorSystem;
+
+impl<'s> System<'s> for NetworkSimulatorSystem {
+    type SystemData = (
+        Read<'s, InputController>,
+        ReadExpect<'s, AppLifeCycle>,
+        ReadExpect<'s, ClientConfig>,
+    );
+
+    fn run(&mut self, (input, lifecycle, config): Self::SystemData) {
+        if input.trigger_or_default("toggle-simulator") == TriggerState::Pressed {
+            if let Some(simulator) = NetworkSimulator::find(&config.host_url) {
+                let enabled = !simulator.is_enabled();
+                simulator.set_enabled(enabled);
+                console_log!("* Network simulator enabled: {}", enabled);
+            }
+        }
+        NetworkSimulator::advance_all(lifecycle.delta_time_seconds());
+    }
+}
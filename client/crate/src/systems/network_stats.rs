@@ -0,0 +1,19 @@
+use crate::resources::network_stats::NetworkStats;
+use oxygengine::prelude::*;
+
+pub struct NetworkStatsSystem;
+
+impl<'s> System<'s> for NetworkStatsSystem {
+    type SystemData = (
+        Read<'s, InputController>,
+        ReadExpect<'s, AppLifeCycle>,
+        WriteExpect<'s, NetworkStats>,
+    );
+
+    fn run(&mut self, (input, lifecycle, mut stats): Self::SystemData) {
+        if input.trigger_or_default("toggle-stats") == TriggerState::Pressed {
+            stats.toggle();
+        }
+        stats.advance(lifecycle.delta_time_seconds());
+    }
+}
@@ -0,0 +1,44 @@
+use crate::components::{collider::Collider, pickup::Pickup, upgrades::Upgrades, PlayerTag};
+use oxygengine::prelude::*;
+
+/// Animates pickups and marks ones touched by local player to be collected.
+pub struct PickupSystem;
+
+impl<'s> System<'s> for PickupSystem {
+    type SystemData = (
+        ReadExpect<'s, AppLifeCycle>,
+        ReadStorage<'s, Collider>,
+        ReadStorage<'s, PlayerTag>,
+        ReadStorage<'s, Upgrades>,
+        WriteStorage<'s, Pickup>,
+        WriteStorage<'s, CompositeTransform>,
+    );
+
+    fn run(
+        &mut self,
+        (lifecycle, colliders, player_tag, upgrades, mut pickups, mut transforms): Self::SystemData,
+    ) {
+        let dt = lifecycle.delta_time_seconds();
+        let players = (&colliders, &transforms, &player_tag, (&upgrades).maybe())
+            .join()
+            .map(|(collider, transform, _, upgrades)| {
+                let bonus = upgrades
+                    .map(|upgrades| upgrades.pickup_radius_bonus())
+                    .unwrap_or_default();
+                (transform.get_translation(), collider.0 + bonus)
+            })
+            .collect::<Vec<_>>();
+
+        for (pickup, collider, transform) in (&mut pickups, &colliders, &mut transforms).join() {
+            pickup.update(dt);
+            transform.set_scale(pickup.pulse().into());
+            let position = transform.get_translation();
+            let touched = players
+                .iter()
+                .any(|(player, radius)| (*player - position).magnitude() <= radius + collider.0);
+            if touched {
+                pickup.touch();
+            }
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use crate::components::{
-    player_controller::PlayerController, speed::Speed, velocity::Velocity, PlayerTag,
+    dash::Dash, player_controller::PlayerController, speed::Speed, time::Time, upgrades::Upgrades,
+    velocity::Velocity, PlayerTag,
 };
 use oxygengine::prelude::*;
 
@@ -10,27 +11,69 @@ impl<'s> System<'s> for PlayerControlSystem {
         Read<'s, InputController>,
         ReadExpect<'s, AppLifeCycle>,
         ReadStorage<'s, Speed>,
-        ReadStorage<'s, PlayerController>,
+        ReadStorage<'s, Upgrades>,
+        WriteStorage<'s, PlayerController>,
         ReadStorage<'s, PlayerTag>,
         WriteStorage<'s, CompositeTransform>,
         WriteStorage<'s, Velocity>,
+        WriteStorage<'s, Dash>,
+        WriteStorage<'s, Time>,
     );
 
     fn run(
         &mut self,
-        (input, lifecycle, speed, controller, tag, mut transforms, mut velocities): Self::SystemData,
+        (
+            input,
+            lifecycle,
+            speed,
+            upgrades,
+            mut controller,
+            tag,
+            mut transforms,
+            mut velocities,
+            mut dashes,
+            mut times,
+        ): Self::SystemData,
     ) {
         let dt = lifecycle.delta_time_seconds() as Scalar;
         let hor = -input.axis_or_default("move-left") + input.axis_or_default("move-right");
         let ver = -input.axis_or_default("move-up") + input.axis_or_default("move-down");
         let offset = Vec2::new(hor, ver);
+        let moving = hor != 0.0 || ver != 0.0;
+        let dash_pressed = input.trigger_or_default("dash").is_on();
 
-        for (_, _, speed, transform, velocity) in
-            (&controller, &tag, &speed, &mut transforms, &mut velocities).join()
+        for (controller, _, speed, upgrades, transform, velocity, dash, time) in (
+            &mut controller,
+            &tag,
+            &speed,
+            (&upgrades).maybe(),
+            &mut transforms,
+            &mut velocities,
+            (&mut dashes).maybe(),
+            (&mut times).maybe(),
+        )
+            .join()
         {
-            let vel = offset * speed.0;
+            let mut multiplier = upgrades
+                .map(|upgrades| upgrades.speed_multiplier())
+                .unwrap_or(1.0);
+            let mut flags = 0;
+            if let Some(dash) = dash {
+                // dash needs direction, server would not see it otherwise.
+                if dash_pressed && moving {
+                    if let Some(time) = time {
+                        dash.start(&mut time.0);
+                    }
+                }
+                multiplier *= dash.speed_multiplier();
+                flags = dash.flags();
+            }
+            let vel = offset * speed.0 * multiplier;
             transform.set_translation(transform.get_translation() + vel * dt);
             velocity.0 = vel;
+            if moving {
+                controller.record(offset, dt, flags, transform.get_translation());
+            }
         }
     }
 }
@@ -0,0 +1,67 @@
+use crate::{
+    components::{
+        dash::Dash, player_controller::PlayerController, speed::Speed, upgrades::Upgrades,
+        PlayerTag,
+    },
+    consts::RECONCILIATION_TOLERANCE,
+    messages::PLAYER_FLAG_DASH,
+};
+use oxygengine::prelude::*;
+
+pub struct ReconciliationSystem;
+
+impl<'s> System<'s> for ReconciliationSystem {
+    type SystemData = (
+        ReadStorage<'s, Speed>,
+        ReadStorage<'s, Dash>,
+        ReadStorage<'s, Upgrades>,
+        WriteStorage<'s, PlayerController>,
+        ReadStorage<'s, PlayerTag>,
+        WriteStorage<'s, CompositeTransform>,
+    );
+
+    fn run(
+        &mut self,
+        (speed, dashes, upgrades, mut controller, tag, mut transforms): Self::SystemData,
+    ) {
+        for (speed, dash, upgrades, controller, _, transform) in (
+            &speed,
+            (&dashes).maybe(),
+            (&upgrades).maybe(),
+            &mut controller,
+            &tag,
+            &mut transforms,
+        )
+            .join()
+        {
+            let correction = match controller.take_correction() {
+                Some(correction) => correction,
+                None => continue,
+            };
+            let predicted = controller.acknowledge(correction.sequence);
+            if let Some(predicted) = predicted {
+                let error = predicted.position - correction.position;
+                if error.sqr_magnitude() <= RECONCILIATION_TOLERANCE * RECONCILIATION_TOLERANCE {
+                    continue;
+                }
+            }
+
+            // replay inputs that server did not process yet on top of authoritative state.
+            let mut position = correction.position;
+            let speed = speed.0
+                * upgrades
+                    .map(|upgrades| upgrades.speed_multiplier())
+                    .unwrap_or(1.0);
+            for pending in controller.pending_mut() {
+                let multiplier = match dash {
+                    Some(dash) if pending.input.flags & PLAYER_FLAG_DASH != 0 => dash.multiplier,
+                    _ => 1.0,
+                };
+                position = position
+                    + pending.input.direction * speed * multiplier * pending.input.delta_time;
+                pending.position = position;
+            }
+            transform.set_translation(position);
+        }
+    }
+}
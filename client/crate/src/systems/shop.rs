@@ -0,0 +1,26 @@
+use crate::{messages::UpgradeKind, resources::shop::Shop};
+use oxygengine::prelude::*;
+
+/// Input trigger buying each upgrade while shop is open.
+const BUY_TRIGGERS: &[(&str, UpgradeKind)] = &[
+    ("buy-speed", UpgradeKind::Speed),
+    ("buy-pickup-radius", UpgradeKind::PickupRadius),
+    ("buy-decay-rate", UpgradeKind::DecayRate),
+];
+
+pub struct ShopSystem;
+
+impl<'s> System<'s> for ShopSystem {
+    type SystemData = (Read<'s, InputController>, WriteExpect<'s, Shop>);
+
+    fn run(&mut self, (input, mut shop): Self::SystemData) {
+        if input.trigger_or_default("toggle-shop") == TriggerState::Pressed {
+            shop.toggle();
+        }
+        for (trigger, kind) in BUY_TRIGGERS {
+            if input.trigger_or_default(trigger) == TriggerState::Pressed {
+                shop.request(*kind);
+            }
+        }
+    }
+}
@@ -1,4 +1,7 @@
-use crate::components::{owned_by::OwnedBy, time::Time, TimerTag};
+use crate::components::{
+    owned_by::OwnedBy, time::Time, time_decay::TimeDecay, upgrades::Upgrades, velocity::Velocity,
+    TimerTag,
+};
 use oxygengine::prelude::*;
 
 pub struct TimeSystem;
@@ -8,6 +11,9 @@ impl<'s> System<'s> for TimeSystem {
         Entities<'s>,
         ReadExpect<'s, AppLifeCycle>,
         WriteStorage<'s, Time>,
+        WriteStorage<'s, TimeDecay>,
+        ReadStorage<'s, Velocity>,
+        ReadStorage<'s, Upgrades>,
         WriteStorage<'s, CompositeRenderable>,
         ReadStorage<'s, TimerTag>,
         ReadStorage<'s, OwnedBy>,
@@ -15,12 +21,40 @@ impl<'s> System<'s> for TimeSystem {
 
     fn run(
         &mut self,
-        (entities, lifecycle, mut timers, mut renderables, timer_tag, owned_by): Self::SystemData,
+        (
+            entities,
+            lifecycle,
+            mut timers,
+            mut decays,
+            velocities,
+            upgrades,
+            mut renderables,
+            timer_tag,
+            owned_by,
+        ): Self::SystemData,
     ) {
-        let dt = lifecycle.delta_time_seconds() as Scalar;
+        let dt = lifecycle.delta_time_seconds();
 
-        for (entity, mut timer) in (&entities, &mut timers).join() {
-            timer.0 -= dt;
+        for (entity, timer, decay, velocity, upgrades) in (
+            &entities,
+            &mut timers,
+            (&mut decays).maybe(),
+            (&velocities).maybe(),
+            (&upgrades).maybe(),
+        )
+            .join()
+        {
+            let moving = velocity
+                .map(|velocity| velocity.0.sqr_magnitude() > 0.0)
+                .unwrap_or(false);
+            let lost = match decay {
+                Some(decay) => decay.update(dt, timer.0, moving),
+                None => dt as Scalar,
+            };
+            let multiplier = upgrades
+                .map(|upgrades| upgrades.decay_multiplier())
+                .unwrap_or(1.0);
+            timer.0 -= lost * multiplier;
             if timer.0 <= 0.0 {
                 // TODO: remove from `GameState::entities`.
                 drop(entities.delete(entity));
@@ -29,14 +63,11 @@ impl<'s> System<'s> for TimeSystem {
 
         for (_, owned_by, renderable) in (&timer_tag, &owned_by, &mut renderables).join() {
             if let Some(time) = timers.get(owned_by.entity()) {
-                match &mut renderable.0 {
-                    Renderable::Text(text) => {
-                        let time = (time.0 as i32).max(0);
-                        let minutes = time / 60;
-                        let seconds = time % 60;
-                        text.text = format!("{:02}:{:02}", minutes, seconds).into();
-                    }
-                    _ => {}
+                if let Renderable::Text(text) = &mut renderable.0 {
+                    let time = (time.0 as i32).max(0);
+                    let minutes = time / 60;
+                    let seconds = time % 60;
+                    text.text = format!("{:02}:{:02}", minutes, seconds).into();
                 }
             }
         }
@@ -0,0 +1,142 @@
+use your_time_is_currency::{
+    components::time_decay::{DecayCurve, TimeDecay},
+    consts::{CAMERA_SCALE, HOST_URL, PLAYER_SPEED, SEND_STATE_DELAY, TIME_DECAY_RATE},
+    resources::client_config::ClientConfig,
+};
+
+#[test]
+fn missing_keys_keep_defaults() {
+    let config = ClientConfig::from_json("{}").unwrap();
+    assert_eq!(config.host_url, HOST_URL);
+    assert_eq!(config.player_speed, PLAYER_SPEED);
+    assert_eq!(config.send_state_delay, SEND_STATE_DELAY);
+    assert!(!config.simulate_network);
+}
+
+#[test]
+fn config_json_overrides_defaults() {
+    let config = ClientConfig::from_json(
+        r#"{ "host_url": "ws://example.com:8090", "player_speed": 300.0 }"#,
+    )
+    .unwrap();
+    assert_eq!(config.host_url, "ws://example.com:8090");
+    assert_eq!(config.player_speed, 300.0);
+    assert_eq!(config.camera_scale, CAMERA_SCALE);
+}
+
+#[test]
+fn query_params_override_config_json() {
+    let mut config =
+        ClientConfig::from_json(r#"{ "player_speed": 300.0, "camera_scale": 2048.0 }"#).unwrap();
+    assert!(config.set("player_speed", "500"));
+    assert!(config.set("simulate_network", "true"));
+    assert_eq!(config.player_speed, 500.0);
+    assert!(config.simulate_network);
+    assert_eq!(config.camera_scale, 2048.0);
+    assert_eq!(config.host_url, HOST_URL);
+}
+
+#[test]
+fn invalid_config_json_is_rejected() {
+    assert!(ClientConfig::from_json("").is_err());
+    assert!(ClientConfig::from_json(r#"{ "player_speed": "fast" }"#).is_err());
+    // unknown keys (e.g. from newer builds) are ignored.
+    let config = ClientConfig::from_json(r#"{ "unknown": 1, "camera_scale": 512.0 }"#).unwrap();
+    assert_eq!(config.camera_scale, 512.0);
+}
+
+#[test]
+fn invalid_query_params_are_ignored() {
+    let mut config = ClientConfig::default();
+    assert!(!config.set("unknown", "1"));
+    assert!(!config.set("player_speed", "fast"));
+    assert!(!config.set("simulate_network", "yes"));
+    assert_eq!(config.player_speed, PLAYER_SPEED);
+    assert!(!config.simulate_network);
+}
+
+#[test]
+fn every_setting_can_be_set_from_query_params() {
+    let defaults = serde_json::to_value(ClientConfig::default()).unwrap();
+    let defaults = defaults.as_object().unwrap();
+    let mut names = defaults.keys().map(String::as_str).collect::<Vec<_>>();
+    let mut settings = ClientConfig::SETTINGS.to_vec();
+    names.sort();
+    settings.sort();
+    assert_eq!(names, settings);
+
+    let mut config = ClientConfig::default();
+    for (name, value) in defaults {
+        let value = match value.as_str() {
+            Some(value) => value.to_owned(),
+            None => value.to_string(),
+        };
+        assert!(config.set(name, &value), "{} = {}", name, value);
+    }
+}
+
+#[test]
+fn time_decay_is_set_from_query_params() {
+    let mut config = ClientConfig::default();
+    assert!(config.set("time_decay", "proportional"));
+    assert!(config.set("time_decay_proportional", "0.02"));
+    assert_eq!(config.time_decay().curve, DecayCurve::Proportional(0.02));
+    // other curves keep their own parameters.
+    assert!(config.set("time_decay", "moving"));
+    assert_ne!(config.time_decay().curve, DecayCurve::Moving(0.02));
+    assert!(config.set("time_decay", "proportional"));
+
+    assert!(!config.set("time_decay", "exponential"));
+    assert_eq!(config.time_decay, "proportional");
+}
+
+#[test]
+fn unknown_time_decay_curve_falls_back_to_constant() {
+    let config = ClientConfig::from_json(r#"{ "time_decay": "exponential" }"#).unwrap();
+    assert_eq!(
+        config.time_decay().curve,
+        DecayCurve::Constant(TIME_DECAY_RATE)
+    );
+}
+
+/// Rates of given time decay curve, over one minute of play from 60 seconds.
+fn decay(config: &ClientConfig, curve: &str, moving: bool) -> (TimeDecay, Vec<f32>) {
+    let mut config = config.clone();
+    assert!(config.set("time_decay", curve));
+    let mut decay = config.time_decay();
+    let mut time = 60.0;
+    let rates = (0..60)
+        .map(|_| {
+            let rate = decay.rate(time, moving);
+            time -= decay.update(1.0, time, moving);
+            rate
+        })
+        .collect();
+    (decay, rates)
+}
+
+#[test]
+fn shipped_time_decay_curves_are_playable() {
+    let shipped = ClientConfig::from_json(include_str!("../../static/config.json")).unwrap();
+    for config in [ClientConfig::default(), shipped] {
+        let (_, rates) = decay(&config, "constant", true);
+        assert!(rates.iter().all(|rate| *rate == 1.0));
+
+        // rich player bleeds faster, but nowhere near whole time left per second.
+        let (curve, rates) = decay(&config, "proportional", false);
+        assert!((1.5..2.0).contains(&rates[0]), "{:?}", rates);
+        assert!(rates.windows(2).all(|pair| pair[1] <= pair[0]));
+        assert_eq!(curve.rate(0.0, false), 1.0);
+
+        // starts like constant decay and grows over match.
+        let (curve, rates) = decay(&config, "accelerating", false);
+        assert_eq!(rates[0], 1.0);
+        assert!(rates.windows(2).all(|pair| pair[1] > pair[0]));
+        assert!(curve.rate(60.0, false) < 2.0);
+
+        let (_, moving) = decay(&config, "moving", true);
+        let (_, standing) = decay(&config, "moving", false);
+        assert!(moving.iter().all(|rate| *rate > 1.0 && *rate < 2.0));
+        assert!(standing.iter().all(|rate| *rate == 1.0));
+    }
+}
@@ -0,0 +1,305 @@
+use oxygengine::prelude::*;
+use proptest::{collection::vec, prelude::*};
+use std::io::Cursor;
+use your_time_is_currency::{
+    messages::{
+        Message, MessageData, MsgHello, MsgInitPlayer, MsgPickup, MsgPlayerCorrection,
+        MsgPlayerInfo, MsgPlayerInput, MsgPlayerState, MsgPlayerStateCompact, MsgPong,
+        MsgPurchaseUpgrade, MsgUpgradePurchased, MsgViewArea, MsgWelcome, ProtocolError,
+        UpgradeKind, COMPACT_POSITION_STEPS, COMPACT_TIME_STEPS, HEADER_SIZE, MESSAGE_VERSION,
+    },
+    net::compact::{StateEncoder, MAX_UNACKNOWLEDGED},
+};
+
+fn scalar() -> impl Strategy<Value = f32> {
+    -1.0e6f32..1.0e6
+}
+
+fn time() -> impl Strategy<Value = f64> {
+    0.0f64..1.0e9
+}
+
+fn vec2() -> impl Strategy<Value = Vec2> {
+    (scalar(), scalar()).prop_map(|(x, y)| Vec2::new(x, y))
+}
+
+fn text() -> impl Strategy<Value = String> {
+    ".{0,64}"
+}
+
+fn player_info() -> impl Strategy<Value = MsgPlayerInfo> {
+    (any::<u32>(), text(), scalar(), vec2()).prop_map(|(id, name, time, position)| MsgPlayerInfo {
+        id,
+        name,
+        time,
+        position,
+    })
+}
+
+fn player_state() -> impl Strategy<Value = MsgPlayerState> {
+    (
+        any::<u32>(),
+        0.0f32..1000.0,
+        vec2(),
+        vec2(),
+        0.0f64..1.0e6,
+        any::<u8>(),
+    )
+        .prop_map(
+            |(id, time, position, velocity, timestamp, flags)| MsgPlayerState {
+                id,
+                time,
+                position,
+                velocity,
+                timestamp,
+                flags,
+            },
+        )
+}
+
+fn player_state_compact() -> impl Strategy<Value = MsgPlayerStateCompact> {
+    (
+        (any::<u32>(), any::<u16>(), any::<u16>(), any::<u32>()),
+        proptest::option::of(any::<u16>()),
+        proptest::option::of(any::<(i16, i16)>()),
+        proptest::option::of(any::<(u16, u16)>()),
+        proptest::option::of(any::<(u8, u16)>()),
+        proptest::option::of(any::<u8>()),
+    )
+        .prop_map(
+            |((id, sequence, baseline, timestamp), time, cell, offset, velocity, flags)| {
+                MsgPlayerStateCompact {
+                    id,
+                    sequence,
+                    baseline,
+                    timestamp,
+                    time,
+                    cell,
+                    offset,
+                    velocity,
+                    flags,
+                }
+            },
+        )
+}
+
+fn upgrade_kind() -> impl Strategy<Value = UpgradeKind> {
+    proptest::sample::select(UpgradeKind::ALL.to_vec())
+}
+
+fn single_message() -> impl Strategy<Value = MessageData> {
+    prop_oneof![
+        (player_info(), text()).prop_map(|(info, resume_token)| {
+            MessageData::InitPlayer(MsgInitPlayer { info, resume_token })
+        }),
+        player_info().prop_map(MessageData::NewPlayer),
+        (any::<u32>(), scalar(), vec2(), vec2(), time(), any::<u8>()).prop_map(
+            |(id, time, position, velocity, timestamp, flags)| {
+                MessageData::PlayerState(MsgPlayerState {
+                    id,
+                    time,
+                    position,
+                    velocity,
+                    timestamp,
+                    flags,
+                })
+            }
+        ),
+        any::<u32>().prop_map(MessageData::PlayerDisconnected),
+        scalar().prop_map(MessageData::PlayerEat),
+        (
+            any::<u32>(),
+            text(),
+            any::<u32>(),
+            proptest::option::of(text())
+        )
+            .prop_map(|(protocol_version, build_id, capabilities, resume_token)| {
+                MessageData::Hello(MsgHello {
+                    protocol_version,
+                    build_id,
+                    capabilities,
+                    resume_token,
+                })
+            }),
+        (any::<u32>(), any::<u32>()).prop_map(|(protocol_version, capabilities)| {
+            MessageData::Welcome(MsgWelcome {
+                protocol_version,
+                capabilities,
+            })
+        }),
+        (any::<u32>(), vec2(), scalar(), any::<u8>()).prop_map(
+            |(sequence, direction, delta_time, flags)| {
+                MessageData::PlayerInput(MsgPlayerInput {
+                    sequence,
+                    direction,
+                    delta_time,
+                    flags,
+                })
+            }
+        ),
+        (any::<u32>(), vec2(), vec2()).prop_map(|(sequence, position, velocity)| {
+            MessageData::PlayerCorrection(MsgPlayerCorrection {
+                sequence,
+                position,
+                velocity,
+            })
+        }),
+        time().prop_map(MessageData::Ping),
+        (time(), time()).prop_map(|(client_time, server_time)| {
+            MessageData::Pong(MsgPong {
+                client_time,
+                server_time,
+            })
+        }),
+        player_state_compact().prop_map(MessageData::PlayerStateCompact),
+        any::<u16>().prop_map(MessageData::PlayerStateAck),
+        (vec2(), vec2()).prop_map(|(center, half_extents)| {
+            MessageData::ViewArea(MsgViewArea {
+                center,
+                half_extents,
+            })
+        }),
+        (any::<u32>(), scalar(), vec2()).prop_map(|(id, time, position)| {
+            MessageData::PickupSpawned(MsgPickup { id, time, position })
+        }),
+        any::<u32>().prop_map(MessageData::PickupDespawned),
+        any::<u32>().prop_map(MessageData::CollectPickup),
+        (upgrade_kind(), any::<u8>()).prop_map(|(kind, level)| {
+            MessageData::PurchaseUpgrade(MsgPurchaseUpgrade { kind, level })
+        }),
+        (upgrade_kind(), any::<u8>(), scalar()).prop_map(|(kind, level, cost)| {
+            MessageData::UpgradePurchased(MsgUpgradePurchased { kind, level, cost })
+        }),
+    ]
+}
+
+fn message() -> impl Strategy<Value = MessageData> {
+    prop_oneof![
+        4 => single_message(),
+        1 => vec(single_message(), 0..8).prop_map(MessageData::Batch),
+    ]
+}
+
+fn frame(id: u32, data: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(HEADER_SIZE + data.len());
+    frame.extend(&id.to_be_bytes());
+    frame.extend(&MESSAGE_VERSION.to_be_bytes());
+    frame.extend(data);
+    frame
+}
+
+proptest! {
+    #[test]
+    fn messages_survive_round_trip(message in message()) {
+        prop_assert_eq!(MessageData::from_frame(&message.to_frame()), Ok(message));
+    }
+
+    #[test]
+    fn arbitrary_frames_do_not_panic(bytes in vec(any::<u8>(), 0..256)) {
+        let _ = MessageData::from_frame(&bytes);
+    }
+
+    #[test]
+    fn compact_state_is_close_to_full_one(state in player_state()) {
+        let decoded = MsgPlayerStateCompact::quantize(&state, 1).to_state();
+        prop_assert_eq!(decoded.id, state.id);
+        prop_assert!((decoded.time - state.time).abs() <= 0.5 / COMPACT_TIME_STEPS + 0.001);
+        let error = (decoded.position - state.position).magnitude();
+        prop_assert!(error <= 1.0 / COMPACT_POSITION_STEPS);
+        prop_assert!((decoded.timestamp - state.timestamp).abs() <= 0.0005 + 1.0e-9);
+        prop_assert_eq!(decoded.flags, state.flags);
+    }
+
+    #[test]
+    fn compact_delta_resolves_to_absolute_state(a in player_state(), b in player_state()) {
+        let baseline = MsgPlayerStateCompact::quantize(&a, 1);
+        let absolute = MsgPlayerStateCompact::quantize(&b, 2);
+        let delta = absolute.delta(&baseline);
+        prop_assert_eq!(delta.resolve(Some(&baseline)), Ok(absolute));
+    }
+
+    #[test]
+    fn decoded_frames_encode_to_same_bytes(id in 1u32..21, data in vec(any::<u8>(), 0..64)) {
+        let frame = frame(id, &data);
+        if let Ok(message) = MessageData::from_frame(&frame) {
+            prop_assert_eq!(message.to_frame(), frame);
+        }
+    }
+}
+
+#[test]
+fn huge_lengths_are_refused() {
+    let mut data = 8u32.to_be_bytes().to_vec();
+    data.extend(&u32::MAX.to_be_bytes());
+    assert_eq!(
+        MessageData::from_frame(&frame(2, &data)),
+        Err(ProtocolError::TooLong(u32::MAX))
+    );
+    assert_eq!(
+        Vec::<u32>::msg_read(&mut Cursor::new(u32::MAX.to_be_bytes())),
+        Err(ProtocolError::TooLong(u32::MAX))
+    );
+}
+
+#[test]
+fn state_encoder_sends_deltas_against_acknowledged_state() {
+    let mut encoder = StateEncoder::default();
+    let mut state = MsgPlayerState {
+        id: 1,
+        time: 60.0,
+        position: Vec2::new(100.0, 200.0),
+        velocity: Vec2::zero(),
+        timestamp: 1.0,
+        flags: 0,
+    };
+    let first = encoder.encode(&state);
+    assert_eq!(first.baseline, 0);
+    state.timestamp = 1.25;
+    assert_eq!(encoder.encode(&state).baseline, 0);
+
+    encoder.acknowledge(first.sequence);
+    state.position.x = 101.0;
+    let delta = encoder.encode(&state);
+    assert_eq!(delta.baseline, first.sequence);
+    assert_eq!(delta.time, None);
+    assert_eq!(delta.cell, None);
+    assert!(delta.offset.is_some());
+    assert_eq!(
+        delta.resolve(Some(&first)).unwrap().to_state().position,
+        state.position
+    );
+}
+
+#[test]
+fn state_encoder_falls_back_to_absolute_states_when_acks_are_lost() {
+    let mut encoder = StateEncoder::default();
+    let mut state = MsgPlayerState {
+        id: 1,
+        time: 60.0,
+        position: Vec2::new(100.0, 200.0),
+        velocity: Vec2::zero(),
+        timestamp: 1.0,
+        flags: 0,
+    };
+    let first = encoder.encode(&state);
+    encoder.acknowledge(first.sequence);
+    for _ in 0..MAX_UNACKNOWLEDGED {
+        state.timestamp += 0.25;
+        assert_eq!(encoder.encode(&state).baseline, first.sequence);
+    }
+
+    // server has received as many states after baseline as it keeps, so it is gone by now.
+    state.timestamp += 0.25;
+    let absolute = encoder.encode(&state);
+    assert_eq!(absolute.baseline, 0);
+    assert_eq!(
+        absolute.resolve(None).unwrap().to_state().position,
+        state.position
+    );
+    state.timestamp += 0.25;
+    assert_eq!(encoder.encode(&state).baseline, 0);
+
+    encoder.acknowledge(absolute.sequence);
+    state.timestamp += 0.25;
+    assert_eq!(encoder.encode(&state).baseline, absolute.sequence);
+}
@@ -0,0 +1,38 @@
+use oxygengine::prelude::*;
+use your_time_is_currency::systems::collision::SpatialHash;
+
+fn sorted(mut items: Vec<(u32, Scalar)>) -> Vec<u32> {
+    items.sort_by_key(|(item, _)| *item);
+    items.into_iter().map(|(item, _)| item).collect()
+}
+
+#[test]
+fn query_finds_overlapping_circles_only() {
+    let mut hash = SpatialHash::new(100.0);
+    hash.insert(1, Vec2::new(0.0, 0.0), 10.0);
+    hash.insert(2, Vec2::new(25.0, 0.0), 10.0);
+    hash.insert(3, Vec2::new(50.0, 0.0), 10.0);
+    assert_eq!(sorted(hash.query(Vec2::new(10.0, 0.0), 5.0)), vec![1, 2]);
+    assert!(hash.query(Vec2::new(500.0, 500.0), 50.0).is_empty());
+}
+
+#[test]
+fn query_looks_into_neighbour_cells() {
+    let mut hash = SpatialHash::new(100.0);
+    hash.insert(1, Vec2::new(-5.0, -5.0), 10.0);
+    hash.insert(2, Vec2::new(99.0, 99.0), 10.0);
+    hash.insert(3, Vec2::new(350.0, 0.0), 200.0);
+    assert_eq!(sorted(hash.query(Vec2::new(5.0, 5.0), 10.0)), vec![1]);
+    assert_eq!(sorted(hash.query(Vec2::new(105.0, 105.0), 10.0)), vec![2]);
+    // big circles are found even if their center is many cells away.
+    assert_eq!(sorted(hash.query(Vec2::new(140.0, 0.0), 20.0)), vec![3]);
+}
+
+#[test]
+fn query_reports_overlap_depth() {
+    let mut hash = SpatialHash::new(64.0);
+    hash.insert(1, Vec2::new(30.0, 40.0), 20.0);
+    let found = hash.query(Vec2::zero(), 40.0);
+    assert_eq!(found.len(), 1);
+    assert!((found[0].1 - 10.0).abs() < 1.0e-4);
+}
@@ -0,0 +1,64 @@
+use your_time_is_currency::{components::dash::Dash, messages::PLAYER_FLAG_DASH};
+
+fn dash() -> Dash {
+    Dash::new(5.0, 0.25, 3.0, 2.0, 10.0)
+}
+
+#[test]
+fn dash_spends_time_and_multiplies_speed() {
+    let mut dash = dash();
+    let mut time = 30.0;
+    assert_eq!(dash.speed_multiplier(), 1.0);
+    assert_eq!(dash.flags(), 0);
+
+    assert!(dash.start(&mut time));
+    assert_eq!(time, 25.0);
+    assert_eq!(dash.speed_multiplier(), 3.0);
+    assert_eq!(dash.flags(), PLAYER_FLAG_DASH);
+
+    dash.update(0.25);
+    assert!(!dash.is_active());
+    assert_eq!(dash.speed_multiplier(), 1.0);
+}
+
+#[test]
+fn dash_waits_for_cooldown() {
+    let mut dash = dash();
+    let mut time = 60.0;
+    assert!(dash.start(&mut time));
+    dash.update(1.0);
+    assert!(!dash.start(&mut time));
+    assert_eq!(time, 55.0);
+    dash.update(1.0);
+    assert!(dash.start(&mut time));
+    assert_eq!(time, 50.0);
+}
+
+#[test]
+fn dash_cannot_go_below_min_time() {
+    let mut dash = dash();
+    let mut time = 14.5;
+    assert!(!dash.can_start(time));
+    assert!(!dash.start(&mut time));
+    assert_eq!(time, 14.5);
+    assert!(!dash.is_active());
+
+    time = 15.0;
+    assert!(dash.start(&mut time));
+    assert_eq!(time, 10.0);
+}
+
+#[test]
+fn remote_dash_follows_state_flags() {
+    let mut dash = dash();
+    dash.set_active(true);
+    assert!(dash.is_active());
+    dash.update(0.125);
+    dash.set_active(true);
+    dash.update(0.125);
+    assert!(!dash.is_active());
+
+    dash.set_active(true);
+    dash.set_active(false);
+    assert!(!dash.is_active());
+}
@@ -0,0 +1,174 @@
+//! Decoding any frame must fail cleanly and allocate memory proportional to frame size.
+
+use proptest::{collection::vec, prelude::*};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+use your_time_is_currency::messages::{
+    MessageData, MsgPlayerInfo, ProtocolError, BATCH_ID, HEADER_SIZE, MAX_LIST_LENGTH,
+    MAX_STRING_LENGTH, MESSAGE_VERSION,
+};
+
+/// Counts bytes allocated by current thread, so tests running in parallel do not interfere.
+struct CountingAlloc;
+
+thread_local! {
+    static ALLOCATED: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.with(|allocated| allocated.set(allocated.get() + layout.size()));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATED.with(|allocated| allocated.set(allocated.get() + new_size));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOC: CountingAlloc = CountingAlloc;
+
+/// Allowed bytes allocated per frame byte, decoded messages are bigger than their encoding
+/// and buffers grow by doubling.
+const ALLOCATION_PER_BYTE: usize = 32;
+/// Allowed bytes allocated regardless of frame size.
+const ALLOCATION_BASE: usize = 1024;
+
+/// Decodes frame, checking that it does not allocate more than it may.
+fn decode(frame: &[u8]) -> Result<MessageData, ProtocolError> {
+    let before = ALLOCATED.with(Cell::get);
+    let result = MessageData::from_frame(frame);
+    let allocated = ALLOCATED.with(Cell::get) - before;
+    assert!(
+        allocated <= frame.len() * ALLOCATION_PER_BYTE + ALLOCATION_BASE,
+        "{} bytes allocated for {} bytes frame",
+        allocated,
+        frame.len()
+    );
+    result
+}
+
+fn header(id: u32) -> Vec<u8> {
+    let mut frame = id.to_be_bytes().to_vec();
+    frame.extend(&MESSAGE_VERSION.to_be_bytes());
+    frame
+}
+
+/// Batch frame that holds another batch frame, `depth` levels deep.
+fn nested_batch(depth: usize) -> Vec<u8> {
+    let level = HEADER_SIZE + 8;
+    let empty = HEADER_SIZE + 4;
+    let mut frame = Vec::with_capacity(depth * level + empty);
+    for inner in (0..depth).rev() {
+        frame.extend(header(BATCH_ID));
+        frame.extend(&1u32.to_be_bytes());
+        frame.extend(&((inner * level + empty) as u32).to_be_bytes());
+    }
+    frame.extend(header(BATCH_ID));
+    frame.extend(&0u32.to_be_bytes());
+    frame
+}
+
+/// `NewPlayer` frame with name of given claimed length, but only `bytes` of it present.
+fn new_player(length: u32, bytes: usize) -> Vec<u8> {
+    let mut frame = header(2);
+    frame.extend(&1u32.to_be_bytes());
+    frame.extend(&length.to_be_bytes());
+    frame.extend(vec![b'a'; bytes]);
+    frame.extend(&[0; 12]);
+    frame
+}
+
+/// Batch claiming `count` items, with only `items` of them present.
+fn batch(count: u32, items: usize) -> Vec<u8> {
+    let item = MessageData::PlayerDisconnected(8).to_frame();
+    let mut frame = header(BATCH_ID);
+    frame.extend(&count.to_be_bytes());
+    for _ in 0..items {
+        frame.extend(&(item.len() as u32).to_be_bytes());
+        frame.extend(&item);
+    }
+    frame
+}
+
+#[test]
+fn nested_batches_are_refused() {
+    for depth in &[1, 2, 64, 10_000, 100_000] {
+        assert_eq!(
+            decode(&nested_batch(*depth)),
+            Err(ProtocolError::NestedBatch)
+        );
+    }
+}
+
+#[test]
+fn strings_near_limit_are_refused_unless_complete() {
+    let length = MAX_STRING_LENGTH;
+    assert_eq!(
+        decode(&new_player(length + 1, 0)),
+        Err(ProtocolError::TooLong(length + 1))
+    );
+    assert_eq!(
+        decode(&new_player(length, 0)),
+        Err(ProtocolError::Truncated)
+    );
+    assert_eq!(
+        decode(&new_player(length, length as usize - 1)),
+        Err(ProtocolError::Truncated)
+    );
+    assert_eq!(
+        decode(&new_player(length, length as usize)),
+        Ok(MessageData::NewPlayer(MsgPlayerInfo {
+            id: 1,
+            name: "a".repeat(length as usize),
+            time: 0.0,
+            position: Default::default(),
+        }))
+    );
+}
+
+#[test]
+fn lists_near_limit_are_refused_unless_complete() {
+    let count = MAX_LIST_LENGTH;
+    assert_eq!(
+        decode(&batch(count + 1, 0)),
+        Err(ProtocolError::TooLong(count + 1))
+    );
+    assert_eq!(decode(&batch(count, 0)), Err(ProtocolError::Truncated));
+    assert_eq!(
+        decode(&batch(count, count as usize - 1)),
+        Err(ProtocolError::Truncated)
+    );
+    assert!(decode(&batch(count, count as usize)).is_ok());
+}
+
+fn frame() -> impl Strategy<Value = Vec<u8>> {
+    // valid header of any message, so body gets decoded.
+    (1u32..21, vec(any::<u8>(), 0..4096)).prop_map(|(id, body)| {
+        let mut frame = header(id);
+        frame.extend(body);
+        frame
+    })
+}
+
+proptest! {
+    #[test]
+    fn arbitrary_frames_allocate_proportionally(frame in frame()) {
+        let _ = decode(&frame);
+    }
+
+    #[test]
+    fn nested_batches_with_garbage_are_refused(depth in 1usize..256, tail in vec(any::<u8>(), 0..64)) {
+        let mut frame = nested_batch(depth);
+        frame.extend(tail);
+        prop_assert!(decode(&frame).is_err());
+    }
+}
@@ -0,0 +1,831 @@
+use oxygengine::prelude::*;
+use std::convert::TryFrom;
+use your_time_is_currency::{
+    components::{
+        enemy_controller::EnemyController, pickup::Pickup, player_controller::PlayerController,
+        time::Time, upgrades::Upgrades, PlayerTag,
+    },
+    consts::{PREY_ALPHA, VIEW_AREA_MARGIN},
+    headless::{add_headless_resources, headless_world},
+    messages::{
+        MessageData, MsgHello, MsgPickup, MsgPlayerInfo, MsgPlayerInput, MsgPlayerState,
+        MsgPurchaseUpgrade, MsgUpgradePurchased, MsgViewArea, UpgradeKind, PLAYER_FLAG_DASH,
+    },
+    net::{
+        link::LinkConditions,
+        local_server::LocalServer,
+        mock::{MockClient, MockHub, MockServer, ScriptedServer},
+        GameClient,
+    },
+    resources::{
+        client_config::ClientConfig, network_clock::NetworkClock, network_stats::NetworkStats,
+        shop::Shop,
+    },
+    states::lobby::LobbyState,
+    systems::{
+        collision::CollisionSystem, dash::DashSystem, follow::FollowSystem,
+        interpolation::InterpolationSystem, local_server::LocalServerSystem,
+        network_clock::NetworkClockSystem, network_simulator::NetworkSimulatorSystem,
+        network_stats::NetworkStatsSystem, pickup::PickupSystem,
+        player_control::PlayerControlSystem, reconciliation::ReconciliationSystem,
+        shop::ShopSystem, time::TimeSystem,
+    },
+};
+
+const DELTA_TIME: f64 = 1.0 / 60.0;
+
+fn setup<S>(url: &str, server: S, conditions: LinkConditions) -> (World, MockHub)
+where
+    S: MockServer + 'static,
+{
+    let hub = MockHub::register(url, server, conditions);
+    let world = headless_world(DELTA_TIME);
+    world.write_resource::<ClientConfig>().host_url = url.to_owned();
+    (world, hub)
+}
+
+/// Creates world like browser app does: storages are registered only by engine and game
+/// systems, not by `headless_world`, so components that nothing registers are caught.
+fn app_world(url: &str) -> World {
+    let mut world = World::new();
+    world.register::<CompositeCamera>();
+    world.register::<CompositeRenderable>();
+    world.register::<CompositeRenderAlpha>();
+    world.register::<CompositeRenderDepth>();
+    world.register::<CompositeTransform>();
+    world.register::<Parent>();
+    world.register::<Tag>();
+    let mut dispatcher = DispatcherBuilder::new()
+        .with(DashSystem, "dash", &[])
+        .with(PlayerControlSystem, "player_control", &[])
+        .with(ReconciliationSystem, "reconciliation", &[])
+        .with(LocalServerSystem, "local_server", &[])
+        .with(NetworkClockSystem, "network_clock", &[])
+        .with(InterpolationSystem, "interpolation", &[])
+        .with(NetworkStatsSystem, "network_stats", &[])
+        .with(NetworkSimulatorSystem, "network_simulator", &[])
+        .with(ShopSystem, "shop", &[])
+        .with(FollowSystem, "follow", &[])
+        .with(PickupSystem, "pickup", &[])
+        .with(CollisionSystem, "collision", &[])
+        .with(TimeSystem, "time", &[])
+        .build();
+    dispatcher.setup(&mut world.res);
+    add_headless_resources(&mut world, DELTA_TIME);
+    world.write_resource::<ClientConfig>().host_url = url.to_owned();
+    world
+}
+
+fn step(world: &mut World, hub: &MockHub, state: &mut dyn State) -> StateChange {
+    hub.advance(DELTA_TIME);
+    world.write_resource::<Network<GameClient>>().process();
+    world.write_resource::<NetworkClock>().advance(DELTA_TIME);
+    let change = state.on_process(world);
+    world.maintain();
+    change
+}
+
+/// Runs state until it wants to change or given time passes.
+fn run(world: &mut World, hub: &MockHub, state: &mut dyn State, time: f64) -> StateChange {
+    let mut elapsed = 0.0;
+    while elapsed < time {
+        match step(world, hub, state) {
+            StateChange::None => {}
+            change => return change,
+        }
+        elapsed += DELTA_TIME;
+    }
+    StateChange::None
+}
+
+fn join_game(world: &mut World, hub: &MockHub) -> Box<dyn State> {
+    let mut lobby = LobbyState::default();
+    lobby.on_enter(world);
+    match run(world, hub, &mut lobby, 1.0) {
+        StateChange::Swap(mut game) => {
+            lobby.on_exit(world);
+            game.on_enter(world);
+            game
+        }
+        _ => panic!("lobby did not enter game"),
+    }
+}
+
+fn send(client: &mut MockClient, message: MessageData) {
+    let id = MessageID::new(message.id(), 0);
+    let data: Vec<u8> = message.into();
+    client.send(id, &data);
+}
+
+fn receive(client: &mut MockClient) -> Vec<MessageData> {
+    client
+        .read_all()
+        .into_iter()
+        .filter_map(|(id, data)| MessageData::try_from((id, data.as_slice())).ok())
+        .collect()
+}
+
+fn enemies_count(world: &World) -> usize {
+    world.read_storage::<EnemyController>().join().count()
+}
+
+fn player_time(world: &World) -> Scalar {
+    (
+        &world.read_storage::<Time>(),
+        &world.read_storage::<PlayerTag>(),
+    )
+        .join()
+        .map(|(time, _)| time.0)
+        .next()
+        .unwrap()
+}
+
+#[test]
+fn lobby_enters_game_after_handshake() {
+    let conditions = LinkConditions {
+        latency: 0.05,
+        jitter: 0.02,
+        ..Default::default()
+    };
+    let (mut world, hub) = setup("mock://handshake", ScriptedServer::default(), conditions);
+    join_game(&mut world, &hub);
+    assert_eq!(hub.connections(), vec![1]);
+    let hello = hub
+        .take_received()
+        .into_iter()
+        .any(|(_, message)| matches!(message, MessageData::Hello(_)));
+    assert!(hello);
+}
+
+#[test]
+fn lobby_refuses_server_with_other_protocol_version() {
+    let server = ScriptedServer {
+        protocol_version: 0,
+        ..Default::default()
+    };
+    let (mut world, hub) = setup("mock://outdated", server, LinkConditions::default());
+    let mut lobby = LobbyState::default();
+    lobby.on_enter(&mut world);
+    assert!(matches!(
+        run(&mut world, &hub, &mut lobby, 1.0),
+        StateChange::None
+    ));
+    assert!(hub.connections().is_empty());
+}
+
+#[test]
+fn game_spawns_and_despawns_enemies() {
+    let (mut world, hub) = setup(
+        "mock://enemies",
+        ScriptedServer::default(),
+        LinkConditions::default(),
+    );
+    let mut game = join_game(&mut world, &hub);
+    let connection = hub.connections()[0];
+
+    hub.send(
+        connection,
+        MessageData::NewPlayer(MsgPlayerInfo {
+            id: 100,
+            name: "Enemy".to_owned(),
+            time: 30.0,
+            position: Vec2::new(300.0, 0.0),
+        }),
+    );
+    run(&mut world, &hub, game.as_mut(), 0.1);
+    assert_eq!(enemies_count(&world), 1);
+
+    hub.send(connection, MessageData::PlayerDisconnected(100));
+    run(&mut world, &hub, game.as_mut(), 0.1);
+    assert_eq!(enemies_count(&world), 0);
+}
+
+#[test]
+fn game_leaves_eating_enemies_to_server() {
+    let (mut world, hub) = setup(
+        "mock://prey",
+        ScriptedServer::default(),
+        LinkConditions::default(),
+    );
+    let mut game = join_game(&mut world, &hub);
+    let connection = hub.connections()[0];
+
+    // colliders overlap, but centers are further apart than server's collision radius.
+    hub.send(
+        connection,
+        MessageData::NewPlayer(MsgPlayerInfo {
+            id: 100,
+            name: "Prey".to_owned(),
+            time: 30.0,
+            position: Vec2::new(150.0, 0.0),
+        }),
+    );
+    run(&mut world, &hub, game.as_mut(), 0.1);
+    CollisionSystem.run_now(&world.res);
+    step(&mut world, &hub, game.as_mut());
+    assert_eq!(enemies_count(&world), 1);
+    let alpha = (
+        &world.read_storage::<CompositeRenderAlpha>(),
+        &world.read_storage::<EnemyController>(),
+    )
+        .join()
+        .map(|(alpha, _)| alpha.0)
+        .next();
+    assert_eq!(alpha, Some(PREY_ALPHA));
+
+    hub.send(connection, MessageData::PlayerDisconnected(100));
+    run(&mut world, &hub, game.as_mut(), 0.1);
+    assert_eq!(enemies_count(&world), 0);
+}
+
+#[test]
+fn game_ignores_enemies_outside_view_area() {
+    let (mut world, hub) = setup(
+        "mock://view-area",
+        ScriptedServer::default(),
+        LinkConditions::default(),
+    );
+    let mut game = join_game(&mut world, &hub);
+    let connection = hub.connections()[0];
+    run(&mut world, &hub, game.as_mut(), 0.1);
+
+    for (id, x) in [(100, 300.0), (101, 100_000.0)] {
+        hub.send(
+            connection,
+            MessageData::NewPlayer(MsgPlayerInfo {
+                id,
+                name: "Enemy".to_owned(),
+                time: 30.0,
+                position: Vec2::new(x, 0.0),
+            }),
+        );
+    }
+    run(&mut world, &hub, game.as_mut(), 0.1);
+    assert_eq!(enemies_count(&world), 1);
+}
+
+#[test]
+fn game_spawns_and_despawns_pickups() {
+    let (mut world, hub) = setup(
+        "mock://pickups",
+        ScriptedServer::default(),
+        LinkConditions::default(),
+    );
+    let mut game = join_game(&mut world, &hub);
+    let connection = hub.connections()[0];
+
+    for id in 1..3 {
+        hub.send(
+            connection,
+            MessageData::PickupSpawned(MsgPickup {
+                id,
+                time: 10.0,
+                position: Vec2::new(500.0, id as Scalar * 100.0),
+            }),
+        );
+    }
+    run(&mut world, &hub, game.as_mut(), 0.1);
+    assert_eq!(world.read_storage::<Pickup>().join().count(), 2);
+
+    hub.send(connection, MessageData::PickupDespawned(1));
+    run(&mut world, &hub, game.as_mut(), 0.1);
+    let ids = world
+        .read_storage::<Pickup>()
+        .join()
+        .map(|pickup| pickup.id)
+        .collect::<Vec<_>>();
+    assert_eq!(ids, vec![2]);
+}
+
+#[test]
+fn game_buys_upgrades_from_shop() {
+    let (mut world, hub) = setup(
+        "mock://shop",
+        ScriptedServer::default(),
+        LinkConditions::default(),
+    );
+    let mut game = join_game(&mut world, &hub);
+    let connection = hub.connections()[0];
+    hub.take_received();
+
+    {
+        let mut shop = world.write_resource::<Shop>();
+        shop.toggle();
+        shop.request(UpgradeKind::Speed);
+    }
+    run(&mut world, &hub, game.as_mut(), 0.1);
+    let requested = hub.take_received().into_iter().any(|(_, message)| {
+        message
+            == MessageData::PurchaseUpgrade(MsgPurchaseUpgrade {
+                kind: UpgradeKind::Speed,
+                level: 1,
+            })
+    });
+    assert!(requested);
+
+    let time = player_time(&world);
+    let purchased = MessageData::UpgradePurchased(MsgUpgradePurchased {
+        kind: UpgradeKind::Speed,
+        level: 1,
+        cost: 10.0,
+    });
+    // second confirmation of the same level is ignored.
+    hub.send(connection, purchased.clone());
+    hub.send(connection, purchased);
+    run(&mut world, &hub, game.as_mut(), 0.1);
+    let level = world
+        .read_storage::<Upgrades>()
+        .join()
+        .map(|upgrades| upgrades.level(UpgradeKind::Speed))
+        .next();
+    assert_eq!(level, Some(1));
+    assert_eq!(player_time(&world), time - 10.0);
+}
+
+#[test]
+fn game_unpacks_batched_messages() {
+    let (mut world, hub) = setup(
+        "mock://batch",
+        ScriptedServer::default(),
+        LinkConditions::default(),
+    );
+    let mut game = join_game(&mut world, &hub);
+    let connection = hub.connections()[0];
+
+    let messages = (100..103)
+        .map(|id| {
+            MessageData::NewPlayer(MsgPlayerInfo {
+                id,
+                name: "Enemy".to_owned(),
+                time: 30.0,
+                position: Vec2::new(300.0, id as Scalar),
+            })
+        })
+        .collect();
+    hub.send(connection, MessageData::Batch(messages));
+    run(&mut world, &hub, game.as_mut(), 0.1);
+    assert_eq!(enemies_count(&world), 3);
+}
+
+#[test]
+fn game_registers_components_it_creates() {
+    let url = "mock://app-world";
+    let hub = MockHub::register(url, ScriptedServer::default(), LinkConditions::default());
+    let mut world = app_world(url);
+    let mut game = join_game(&mut world, &hub);
+    hub.send(
+        hub.connections()[0],
+        MessageData::NewPlayer(MsgPlayerInfo {
+            id: 100,
+            name: "enemy".to_owned(),
+            time: 30.0,
+            position: Vec2::new(100.0, 0.0),
+        }),
+    );
+    run(&mut world, &hub, game.as_mut(), 0.1);
+    assert_eq!(enemies_count(&world), 1);
+}
+
+#[test]
+fn game_skips_malformed_frames() {
+    let (mut world, hub) = setup(
+        "mock://malformed",
+        ScriptedServer::default(),
+        LinkConditions::default(),
+    );
+    let mut game = join_game(&mut world, &hub);
+    let connection = hub.connections()[0];
+
+    hub.send_raw(connection, MessageID::new(2, 0), vec![0, 0]);
+    hub.send_raw(connection, MessageID::new(1000, 0), vec![]);
+    assert!(matches!(
+        run(&mut world, &hub, game.as_mut(), 0.1),
+        StateChange::None
+    ));
+    assert_eq!(world.read_resource::<NetworkStats>().malformed_count(), 2);
+}
+
+#[test]
+fn game_counts_dropped_frames() {
+    let (mut world, hub) = setup(
+        "mock://dropped",
+        ScriptedServer::default(),
+        LinkConditions::default(),
+    );
+    let mut game = join_game(&mut world, &hub);
+    let connection = hub.connections()[0];
+
+    hub.set_conditions(LinkConditions {
+        loss: 1.0,
+        ..Default::default()
+    });
+    hub.send(connection, MessageData::PlayerDisconnected(100));
+    hub.set_conditions(LinkConditions::default());
+    hub.send(
+        connection,
+        MessageData::PlayerState(MsgPlayerState {
+            id: 100,
+            time: 30.0,
+            position: Vec2::zero(),
+            velocity: Vec2::zero(),
+            timestamp: 0.0,
+            flags: 0,
+        }),
+    );
+    run(&mut world, &hub, game.as_mut(), 0.1);
+    assert_eq!(world.read_resource::<NetworkStats>().dropped_count(), 2);
+}
+
+#[test]
+fn game_skips_malformed_frames_while_reconnecting() {
+    let (mut world, hub) = setup(
+        "mock://malformed-reconnect",
+        ScriptedServer::default(),
+        LinkConditions::default(),
+    );
+    let mut game = join_game(&mut world, &hub);
+
+    hub.kick(hub.connections()[0]);
+    step(&mut world, &hub, game.as_mut());
+    step(&mut world, &hub, game.as_mut());
+    let connection = hub.connections()[0];
+    hub.send_raw(connection, MessageID::new(1000, 0), vec![]);
+    step(&mut world, &hub, game.as_mut());
+    assert_eq!(world.read_resource::<NetworkStats>().malformed_count(), 1);
+}
+
+#[test]
+fn game_resumes_session_after_connection_loss() {
+    let (mut world, hub) = setup(
+        "mock://resume",
+        LocalServer::default(),
+        LinkConditions::default(),
+    );
+    let mut game = join_game(&mut world, &hub);
+    let (player, id) = {
+        let entities = world.entities();
+        let controllers = world.read_storage::<PlayerController>();
+        (&entities, &controllers)
+            .join()
+            .map(|(entity, controller)| (entity, controller.id()))
+            .next()
+            .unwrap()
+    };
+    hub.take_received();
+
+    hub.drop_connection(hub.connections()[0]);
+    assert!(matches!(
+        run(&mut world, &hub, game.as_mut(), 1.0),
+        StateChange::None
+    ));
+    let tokens = hub
+        .take_received()
+        .into_iter()
+        .filter_map(|(_, message)| match message {
+            MessageData::Hello(hello) => Some(hello.resume_token),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(tokens.len(), 1);
+    assert!(tokens[0].is_some());
+    assert_eq!(hub.connections().len(), 1);
+
+    // same player came back instead of a new one joining.
+    assert!(world.is_alive(player));
+    let controllers = world.read_storage::<PlayerController>();
+    assert_eq!(
+        controllers.get(player).map(|controller| controller.id()),
+        Some(id)
+    );
+    assert_eq!(controllers.join().count(), 1);
+}
+
+#[test]
+fn game_gives_up_reconnecting_after_grace_period() {
+    let url = "mock://timeout";
+    let (mut world, hub) = setup(url, ScriptedServer::default(), LinkConditions::default());
+    let mut game = join_game(&mut world, &hub);
+
+    hub.kick(hub.connections()[0]);
+    MockHub::unregister(url);
+    assert!(matches!(
+        run(&mut world, &hub, game.as_mut(), 20.0),
+        StateChange::Swap(_)
+    ));
+}
+
+#[test]
+fn local_server_introduces_players_to_each_other() {
+    let url = "mock://local-match";
+    let (mut first, hub) = setup(url, LocalServer::default(), LinkConditions::default());
+    let mut first_game = join_game(&mut first, &hub);
+    let mut second = headless_world(DELTA_TIME);
+    second.write_resource::<ClientConfig>().host_url = url.to_owned();
+    let mut second_game = join_game(&mut second, &hub);
+
+    run(&mut second, &hub, second_game.as_mut(), 1.5);
+    run(&mut first, &hub, first_game.as_mut(), 0.1);
+    assert_eq!(enemies_count(&first), 1);
+    assert_eq!(enemies_count(&second), 1);
+}
+
+#[test]
+fn local_server_lets_older_player_eat_younger() {
+    let url = "mock://local-eat";
+    let hub = MockHub::register(url, LocalServer::default(), LinkConditions::default());
+    let mut older = MockClient::open(url).unwrap();
+    let mut younger = MockClient::open(url).unwrap();
+    for (client, time, position) in [
+        (&mut older, 50.0, Vec2::zero()),
+        (&mut younger, 10.0, Vec2::new(50.0, 0.0)),
+    ] {
+        send(client, MessageData::Hello(MsgHello::new(None)));
+        send(
+            client,
+            MessageData::PlayerState(MsgPlayerState {
+                id: 0,
+                time,
+                position,
+                velocity: Vec2::zero(),
+                timestamp: 0.0,
+                flags: 0,
+            }),
+        );
+    }
+    hub.advance(0.1);
+
+    let eaten = receive(&mut older)
+        .into_iter()
+        .any(|message| message == MessageData::PlayerEat(10.0));
+    assert!(eaten);
+    assert!(matches!(older.state(), ClientState::Open));
+    assert!(matches!(younger.state(), ClientState::Closed));
+}
+
+#[test]
+fn local_server_hides_players_outside_view_area() {
+    let url = "mock://local-view";
+    let hub = MockHub::register(url, LocalServer::default(), LinkConditions::default());
+    let mut near = MockClient::open(url).unwrap();
+    let mut far = MockClient::open(url).unwrap();
+    for (client, position) in [
+        (&mut near, Vec2::zero()),
+        (&mut far, Vec2::new(10_000.0, 0.0)),
+    ] {
+        send(client, MessageData::Hello(MsgHello::new(None)));
+        send(
+            client,
+            MessageData::ViewArea(MsgViewArea {
+                center: position,
+                half_extents: Vec2::new(1000.0, 1000.0),
+            }),
+        );
+        send(
+            client,
+            MessageData::PlayerState(MsgPlayerState {
+                id: 0,
+                time: 50.0,
+                position,
+                velocity: Vec2::zero(),
+                timestamp: 0.0,
+                flags: 0,
+            }),
+        );
+    }
+    hub.advance(1.5);
+
+    for client in [&mut near, &mut far] {
+        let introduced = receive(client)
+            .into_iter()
+            .any(|message| matches!(message, MessageData::NewPlayer(_)));
+        assert!(!introduced);
+    }
+}
+
+#[test]
+fn local_server_sends_players_within_view_area_margin() {
+    let url = "mock://local-view-margin";
+    let hub = MockHub::register(url, LocalServer::default(), LinkConditions::default());
+    let mut inside = MockClient::open(url).unwrap();
+    let mut edge = MockClient::open(url).unwrap();
+    // client keeps enemies until they are past margin, so server has to keep sending them.
+    for (client, position) in [
+        (&mut inside, Vec2::zero()),
+        (&mut edge, Vec2::new(1000.0 + VIEW_AREA_MARGIN * 0.5, 0.0)),
+    ] {
+        send(client, MessageData::Hello(MsgHello::new(None)));
+        send(
+            client,
+            MessageData::ViewArea(MsgViewArea {
+                center: Vec2::zero(),
+                half_extents: Vec2::new(1000.0, 1000.0),
+            }),
+        );
+        send(
+            client,
+            MessageData::PlayerState(MsgPlayerState {
+                id: 0,
+                time: 50.0,
+                position,
+                velocity: Vec2::zero(),
+                timestamp: 0.0,
+                flags: 0,
+            }),
+        );
+    }
+    hub.advance(1.5);
+
+    for client in [&mut inside, &mut edge] {
+        let introduced = receive(client)
+            .into_iter()
+            .any(|message| matches!(message, MessageData::NewPlayer(_)));
+        assert!(introduced);
+    }
+}
+
+#[test]
+fn local_server_gives_time_for_collected_pickups() {
+    let url = "mock://local-pickup";
+    let hub = MockHub::register(url, LocalServer::default(), LinkConditions::default());
+    let mut client = MockClient::open(url).unwrap();
+    send(&mut client, MessageData::Hello(MsgHello::new(None)));
+    hub.advance(1.0);
+    let pickup = receive(&mut client)
+        .into_iter()
+        .find_map(|message| match message {
+            MessageData::PickupSpawned(pickup) => Some(pickup),
+            _ => None,
+        })
+        .expect("no pickup spawned");
+
+    let state = |position| {
+        MessageData::PlayerState(MsgPlayerState {
+            id: 0,
+            time: 50.0,
+            position,
+            velocity: Vec2::zero(),
+            timestamp: 0.0,
+            flags: 0,
+        })
+    };
+    send(&mut client, state(pickup.position + Vec2::new(1000.0, 0.0)));
+    send(&mut client, MessageData::CollectPickup(pickup.id));
+    hub.advance(0.0);
+    assert!(receive(&mut client).is_empty());
+
+    send(&mut client, state(pickup.position));
+    send(&mut client, MessageData::CollectPickup(pickup.id));
+    hub.advance(0.0);
+    let messages = receive(&mut client);
+    assert!(messages.contains(&MessageData::PlayerEat(pickup.time)));
+    assert!(messages.contains(&MessageData::PickupDespawned(pickup.id)));
+}
+
+#[test]
+fn local_server_validates_dashes() {
+    let url = "mock://local-dash";
+    let hub = MockHub::register(url, LocalServer::default(), LinkConditions::default());
+    let mut client = MockClient::open(url).unwrap();
+    send(&mut client, MessageData::Hello(MsgHello::new(None)));
+    hub.advance(1.0);
+    receive(&mut client);
+
+    let state = |time| {
+        MessageData::PlayerState(MsgPlayerState {
+            id: 0,
+            time,
+            position: Vec2::zero(),
+            velocity: Vec2::zero(),
+            timestamp: 0.0,
+            flags: 0,
+        })
+    };
+    let mut sequence = 0;
+    let mut move_right = |client: &mut MockClient, flags| {
+        sequence += 1;
+        send(
+            client,
+            MessageData::PlayerInput(MsgPlayerInput {
+                sequence,
+                direction: Vec2::new(1.0, 0.0),
+                delta_time: 0.1,
+                flags,
+            }),
+        );
+        hub.advance(0.2);
+        receive(client)
+            .into_iter()
+            .filter_map(|message| match message {
+                MessageData::PlayerCorrection(correction) => Some(correction.position.x),
+                _ => None,
+            })
+            .next_back()
+            .expect("no correction")
+    };
+
+    // too little time left.
+    send(&mut client, state(12.0));
+    assert!((move_right(&mut client, PLAYER_FLAG_DASH) - 40.0).abs() < 1.0e-3);
+    send(&mut client, state(50.0));
+    assert!((move_right(&mut client, PLAYER_FLAG_DASH) - 160.0).abs() < 1.0e-3);
+    assert!((move_right(&mut client, 0) - 200.0).abs() < 1.0e-3);
+    // still cooling down.
+    assert!((move_right(&mut client, PLAYER_FLAG_DASH) - 240.0).abs() < 1.0e-3);
+}
+
+#[test]
+fn local_server_moves_players_with_configured_rules() {
+    let url = "mock://local-config";
+    let config = ClientConfig {
+        player_speed: 800.0,
+        dash_multiplier: 2.0,
+        ..Default::default()
+    };
+    let hub = MockHub::register(
+        url,
+        LocalServer::with_config(1, &config),
+        LinkConditions::default(),
+    );
+    let mut client = MockClient::open(url).unwrap();
+    send(&mut client, MessageData::Hello(MsgHello::new(None)));
+    send(
+        &mut client,
+        MessageData::PlayerState(MsgPlayerState {
+            id: 0,
+            time: 50.0,
+            position: Vec2::zero(),
+            velocity: Vec2::zero(),
+            timestamp: 0.0,
+            flags: 0,
+        }),
+    );
+    hub.advance(1.0);
+    receive(&mut client);
+
+    send(
+        &mut client,
+        MessageData::PlayerInput(MsgPlayerInput {
+            sequence: 1,
+            direction: Vec2::new(1.0, 0.0),
+            delta_time: 0.1,
+            flags: PLAYER_FLAG_DASH,
+        }),
+    );
+    hub.advance(0.2);
+    let position = receive(&mut client)
+        .into_iter()
+        .filter_map(|message| match message {
+            MessageData::PlayerCorrection(correction) => Some(correction.position.x),
+            _ => None,
+        })
+        .next_back()
+        .expect("no correction");
+    assert!((position - 160.0).abs() < 1.0e-3);
+}
+
+#[test]
+fn local_server_sells_upgrades() {
+    let url = "mock://local-upgrades";
+    let hub = MockHub::register(url, LocalServer::default(), LinkConditions::default());
+    let mut client = MockClient::open(url).unwrap();
+    send(&mut client, MessageData::Hello(MsgHello::new(None)));
+    hub.advance(1.0);
+    receive(&mut client);
+
+    let purchase = |level| {
+        MessageData::PurchaseUpgrade(MsgPurchaseUpgrade {
+            kind: UpgradeKind::DecayRate,
+            level,
+        })
+    };
+    // skipping level is refused.
+    send(&mut client, purchase(2));
+    send(&mut client, purchase(1));
+    send(&mut client, purchase(1));
+    hub.advance(0.0);
+    let confirmed = receive(&mut client)
+        .into_iter()
+        .filter(|message| matches!(message, MessageData::UpgradePurchased(_)))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        confirmed,
+        vec![MessageData::UpgradePurchased(MsgUpgradePurchased {
+            kind: UpgradeKind::DecayRate,
+            level: 1,
+            cost: 10.0,
+        })]
+    );
+
+    // 50 seconds left, next level costs 20 and the one after 30.
+    send(&mut client, purchase(2));
+    send(&mut client, purchase(3));
+    hub.advance(0.0);
+    let levels = receive(&mut client)
+        .into_iter()
+        .filter_map(|message| match message {
+            MessageData::UpgradePurchased(purchased) => Some(purchased.level),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(levels, vec![2]);
+}
@@ -0,0 +1,76 @@
+use your_time_is_currency::resources::network_clock::NetworkClock;
+
+fn assert_near(value: f64, expected: f64) {
+    assert!(
+        (value - expected).abs() < 1.0e-9,
+        "{} is not {}",
+        value,
+        expected
+    );
+}
+
+/// Clock that got first pong at local time 1.0 for ping sent at 0.8, stamped by server at 10.0.
+fn synchronized() -> NetworkClock {
+    let mut clock = NetworkClock::default();
+    clock.advance(1.0);
+    clock.on_pong(0.8, 10.0);
+    clock
+}
+
+#[test]
+fn first_pong_is_taken_as_is() {
+    let mut clock = NetworkClock::default();
+    assert!(!clock.is_synchronized());
+    clock.advance(1.0);
+    clock.on_pong(0.8, 10.0);
+
+    assert!(clock.is_synchronized());
+    assert_near(clock.rtt(), 0.2);
+    assert_near(clock.jitter(), 0.0);
+    // server stamped pong halfway through round trip.
+    assert_near(clock.offset(), 9.1);
+    assert_near(clock.server_time(), 10.1);
+}
+
+#[test]
+fn later_pongs_are_smoothed() {
+    let mut clock = synchronized();
+    clock.advance(1.0);
+    clock.on_pong(1.6, 11.0);
+
+    // sample: rtt 0.4, offset 9.2; smoothed values move a tenth of the way towards it.
+    assert_near(clock.rtt(), 0.22);
+    assert_near(clock.jitter(), 0.02);
+    assert_near(clock.offset(), 9.11);
+    assert_near(clock.server_time(), 11.11);
+
+    clock.advance(1.0);
+    clock.on_pong(2.8, 12.0);
+
+    // sample: rtt 0.2, offset 9.1.
+    assert_near(clock.rtt(), 0.22 + (0.2 - 0.22) * 0.1);
+    assert_near(clock.jitter(), 0.02 + (0.02 - 0.02) * 0.1);
+    assert_near(clock.offset(), 9.11 + (9.1 - 9.11) * 0.1);
+}
+
+#[test]
+fn steady_pongs_have_no_jitter() {
+    let mut clock = synchronized();
+    for _ in 0..10 {
+        let sent = clock.local_time();
+        clock.advance(0.2);
+        clock.on_pong(sent, sent + 9.2);
+    }
+    assert_near(clock.rtt(), 0.2);
+    assert_near(clock.jitter(), 0.0);
+    assert_near(clock.offset(), 9.1);
+}
+
+#[test]
+fn pong_from_future_counts_as_zero_rtt() {
+    let mut clock = NetworkClock::default();
+    clock.advance(1.0);
+    clock.on_pong(1.5, 4.0);
+    assert_near(clock.rtt(), 0.0);
+    assert_near(clock.offset(), 3.0);
+}
@@ -0,0 +1,54 @@
+use your_time_is_currency::resources::network_stats::{NetworkStats, Traffic};
+
+#[test]
+fn rates_are_measured_over_full_window() {
+    let mut stats = NetworkStats::default();
+    for _ in 0..4 {
+        stats.received(20);
+        stats.sent(12);
+        stats.advance(0.125);
+    }
+    assert_eq!(stats.incoming_rate(), Traffic::default());
+
+    for _ in 0..4 {
+        stats.advance(0.125);
+    }
+    assert_eq!(
+        stats.incoming_rate(),
+        Traffic {
+            packets: 4,
+            bytes: 80
+        }
+    );
+    assert_eq!(
+        stats.outgoing_rate(),
+        Traffic {
+            packets: 4,
+            bytes: 48
+        }
+    );
+
+    stats.advance(1.0);
+    assert_eq!(stats.incoming_rate(), Traffic::default());
+}
+
+#[test]
+fn counters_are_kept_across_windows() {
+    let mut stats = NetworkStats::default();
+    stats.malformed();
+    stats.dropped(1);
+    stats.dropped(2);
+    stats.advance(2.5);
+    assert_eq!(stats.malformed_count(), 1);
+    assert_eq!(stats.dropped_count(), 3);
+}
+
+#[test]
+fn overlay_is_toggled() {
+    let mut stats = NetworkStats::default();
+    assert!(!stats.visible);
+    stats.toggle();
+    assert!(stats.visible);
+    stats.toggle();
+    assert!(!stats.visible);
+}
@@ -0,0 +1,36 @@
+use your_time_is_currency::{
+    components::pickup::Pickup,
+    consts::{PICKUP_PULSE_RATE, PICKUP_PULSE_SCALE, PICKUP_REQUEST_DELAY},
+};
+
+#[test]
+fn touch_requests_collection_once_per_delay() {
+    let mut pickup = Pickup::new(1, 10.0);
+    assert!(!pickup.take_request());
+    pickup.touch();
+    pickup.touch();
+    assert!(pickup.take_request());
+    assert!(!pickup.take_request());
+
+    pickup.update(PICKUP_REQUEST_DELAY * 0.5);
+    pickup.touch();
+    assert!(!pickup.take_request());
+    pickup.update(PICKUP_REQUEST_DELAY * 0.5);
+    pickup.touch();
+    assert!(pickup.take_request());
+}
+
+#[test]
+fn pulse_stays_within_scale() {
+    let mut pickup = Pickup::new(1, 10.0);
+    assert_eq!(pickup.pulse(), 1.0);
+    let step = 1.0 / PICKUP_PULSE_RATE / 16.0;
+    let mut max = 1.0f32;
+    for _ in 0..16 {
+        pickup.update(step);
+        let pulse = pickup.pulse();
+        assert!((pulse - 1.0).abs() <= PICKUP_PULSE_SCALE + 1.0e-5);
+        max = max.max(pulse);
+    }
+    assert!((max - 1.0 - PICKUP_PULSE_SCALE).abs() < 1.0e-3);
+}
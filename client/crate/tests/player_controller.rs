@@ -0,0 +1,58 @@
+use oxygengine::prelude::*;
+use your_time_is_currency::{
+    components::player_controller::PlayerController,
+    consts::{MAX_INPUT_DELTA, MAX_INPUT_HISTORY},
+    messages::PLAYER_FLAG_DASH,
+};
+
+const DELTA_TIME: Scalar = 1.0 / 60.0;
+
+#[test]
+fn unsent_inputs_with_same_direction_are_merged() {
+    let mut controller = PlayerController::new(1);
+    let right = Vec2::new(1.0, 0.0);
+    for _ in 0..3 {
+        controller.record(right, DELTA_TIME, 0, Vec2::zero());
+    }
+    controller.record(right, DELTA_TIME, PLAYER_FLAG_DASH, Vec2::zero());
+    controller.record(Vec2::new(0.0, 1.0), DELTA_TIME, 0, Vec2::zero());
+
+    let inputs = controller.drain_outbox();
+    assert_eq!(inputs.len(), 3);
+    assert_eq!(inputs[0].sequence, 3);
+    assert!((inputs[0].delta_time - DELTA_TIME * 3.0).abs() < 1.0e-6);
+    assert_eq!(inputs[1].sequence, 4);
+    assert_eq!(inputs[1].flags, PLAYER_FLAG_DASH);
+    assert_eq!(inputs[2].sequence, 5);
+    // every frame is still kept for replay.
+    assert_eq!(controller.pending_count(), 5);
+    assert!(controller.drain_outbox().is_empty());
+}
+
+#[test]
+fn merged_input_never_exceeds_server_limit() {
+    let mut controller = PlayerController::new(1);
+    for _ in 0..60 {
+        controller.record(Vec2::new(1.0, 0.0), DELTA_TIME, 0, Vec2::zero());
+    }
+    let inputs = controller.drain_outbox();
+    assert!(inputs.len() >= 10);
+    assert!(inputs
+        .iter()
+        .all(|input| input.delta_time <= MAX_INPUT_DELTA));
+    let total = inputs.iter().map(|input| input.delta_time).sum::<Scalar>();
+    assert!((total - 1.0).abs() < 1.0e-4);
+    assert_eq!(inputs.last().unwrap().sequence, 60);
+}
+
+#[test]
+fn history_is_capped_without_corrections() {
+    let mut controller = PlayerController::new(1);
+    for _ in 0..MAX_INPUT_HISTORY * 2 {
+        controller.record(Vec2::new(1.0, 0.0), DELTA_TIME, 0, Vec2::zero());
+        controller.drain_outbox();
+    }
+    assert_eq!(controller.pending_count(), MAX_INPUT_HISTORY);
+    let oldest = controller.pending_mut().next().unwrap().input.sequence;
+    assert_eq!(oldest as usize, MAX_INPUT_HISTORY + 1);
+}
@@ -0,0 +1,361 @@
+//! Golden frames written by `server/fixtures.js` with the same helpers server uses, so any
+//! layout drift between `messages.rs` and Node wire format fails here.
+
+use oxygengine::prelude::*;
+use std::convert::TryFrom;
+use your_time_is_currency::messages::{
+    MessageData, MsgHello, MsgInitPlayer, MsgPickup, MsgPlayerCorrection, MsgPlayerInfo,
+    MsgPlayerInput, MsgPlayerState, MsgPlayerStateCompact, MsgPong, MsgPurchaseUpgrade,
+    MsgUpgradePurchased, MsgViewArea, MsgWelcome, ProtocolError, UpgradeKind, HEADER_SIZE,
+    PLAYER_FLAG_DASH,
+};
+
+macro_rules! fixture {
+    ($name:expr) => {
+        &include_bytes!(concat!("fixtures/", $name, ".bin"))[..]
+    };
+}
+
+fn assert_frame(frame: &[u8], expected: MessageData) {
+    assert_eq!(MessageData::from_frame(frame), Ok(expected.clone()));
+    assert_eq!(expected.to_frame(), frame);
+}
+
+#[test]
+fn init_player() {
+    assert_frame(
+        fixture!("init_player"),
+        MessageData::InitPlayer(MsgInitPlayer {
+            info: MsgPlayerInfo {
+                id: 7,
+                name: "Ada".to_owned(),
+                time: 59.5,
+                position: Vec2::new(128.25, -64.5),
+            },
+            resume_token: "a1b2c3".to_owned(),
+        }),
+    );
+}
+
+#[test]
+fn new_player() {
+    assert_frame(
+        fixture!("new_player"),
+        MessageData::NewPlayer(MsgPlayerInfo {
+            id: 8,
+            name: "Grace".to_owned(),
+            time: 30.25,
+            position: Vec2::new(-512.0, 256.75),
+        }),
+    );
+}
+
+#[test]
+fn player_state() {
+    assert_frame(
+        fixture!("player_state"),
+        MessageData::PlayerState(MsgPlayerState {
+            id: 7,
+            time: 42.5,
+            position: Vec2::new(10.5, -20.25),
+            velocity: Vec2::new(400.0, -0.5),
+            timestamp: 12.375,
+            flags: PLAYER_FLAG_DASH,
+        }),
+    );
+}
+
+#[test]
+fn player_disconnected() {
+    assert_frame(
+        fixture!("player_disconnected"),
+        MessageData::PlayerDisconnected(8),
+    );
+}
+
+#[test]
+fn player_eat() {
+    assert_frame(fixture!("player_eat"), MessageData::PlayerEat(30.25));
+}
+
+#[test]
+fn hello() {
+    assert_frame(
+        fixture!("hello"),
+        MessageData::Hello(MsgHello {
+            protocol_version: 2,
+            build_id: "0.1.0".to_owned(),
+            capabilities: 0,
+            resume_token: Some("a1b2c3".to_owned()),
+        }),
+    );
+    assert_frame(
+        fixture!("hello_without_token"),
+        MessageData::Hello(MsgHello {
+            protocol_version: 2,
+            build_id: "0.1.0".to_owned(),
+            capabilities: 0,
+            resume_token: None,
+        }),
+    );
+}
+
+#[test]
+fn welcome() {
+    assert_frame(
+        fixture!("welcome"),
+        MessageData::Welcome(MsgWelcome {
+            protocol_version: 2,
+            capabilities: 0,
+        }),
+    );
+}
+
+#[test]
+fn player_input() {
+    assert_frame(
+        fixture!("player_input"),
+        MessageData::PlayerInput(MsgPlayerInput {
+            sequence: 3,
+            direction: Vec2::new(1.0, -1.0),
+            delta_time: 0.015_625,
+            flags: PLAYER_FLAG_DASH,
+        }),
+    );
+}
+
+#[test]
+fn player_correction() {
+    assert_frame(
+        fixture!("player_correction"),
+        MessageData::PlayerCorrection(MsgPlayerCorrection {
+            sequence: 3,
+            position: Vec2::new(100.5, -50.25),
+            velocity: Vec2::new(400.0, -400.0),
+        }),
+    );
+}
+
+#[test]
+fn ping() {
+    assert_frame(fixture!("ping"), MessageData::Ping(1.5));
+}
+
+#[test]
+fn pong() {
+    assert_frame(
+        fixture!("pong"),
+        MessageData::Pong(MsgPong {
+            client_time: 1.5,
+            server_time: 1234.125,
+        }),
+    );
+}
+
+#[test]
+fn player_state_compact() {
+    let absolute = MsgPlayerStateCompact {
+        id: 7,
+        sequence: 5,
+        baseline: 0,
+        timestamp: 12375,
+        time: Some(425),
+        cell: Some((0, -1)),
+        offset: Some((672, 63872)),
+        velocity: Some((192, 6400)),
+        flags: Some(0),
+    };
+    assert_frame(
+        fixture!("player_state_compact"),
+        MessageData::PlayerStateCompact(absolute.clone()),
+    );
+    let delta = MsgPlayerStateCompact {
+        id: 7,
+        sequence: 6,
+        baseline: 5,
+        timestamp: 12625,
+        time: None,
+        cell: None,
+        offset: Some((700, 63872)),
+        velocity: None,
+        flags: Some(PLAYER_FLAG_DASH),
+    };
+    assert_frame(
+        fixture!("player_state_compact_delta"),
+        MessageData::PlayerStateCompact(delta.clone()),
+    );
+
+    let state = delta.resolve(Some(&absolute)).unwrap().to_state();
+    assert_eq!(state.id, 7);
+    assert_eq!(state.time, 42.5);
+    assert_eq!(state.position, Vec2::new(10.9375, -26.0));
+    assert!((state.velocity - Vec2::new(0.0, -400.0)).magnitude() < 0.01);
+    assert_eq!(state.timestamp, 12.625);
+    assert_eq!(state.flags, PLAYER_FLAG_DASH);
+    assert_eq!(delta.resolve(None), Err(ProtocolError::UnknownBaseline(5)));
+}
+
+#[test]
+fn player_state_ack() {
+    assert_frame(fixture!("player_state_ack"), MessageData::PlayerStateAck(5));
+}
+
+#[test]
+fn view_area() {
+    let area = MsgViewArea {
+        center: Vec2::new(100.5, -200.25),
+        half_extents: Vec2::new(640.0, 360.0),
+    };
+    assert_frame(fixture!("view_area"), MessageData::ViewArea(area.clone()));
+    assert!(area.contains(Vec2::new(740.0, 150.0), 0.0));
+    assert!(!area.contains(Vec2::new(750.0, 150.0), 0.0));
+    assert!(area.contains(Vec2::new(750.0, 150.0), 10.0));
+}
+
+#[test]
+fn pickups() {
+    assert_frame(
+        fixture!("pickup_spawned"),
+        MessageData::PickupSpawned(MsgPickup {
+            id: 3,
+            time: 10.0,
+            position: Vec2::new(128.5, -64.25),
+        }),
+    );
+    assert_frame(
+        fixture!("pickup_despawned"),
+        MessageData::PickupDespawned(3),
+    );
+    assert_frame(fixture!("collect_pickup"), MessageData::CollectPickup(3));
+}
+
+#[test]
+fn upgrades() {
+    assert_frame(
+        fixture!("purchase_upgrade"),
+        MessageData::PurchaseUpgrade(MsgPurchaseUpgrade {
+            kind: UpgradeKind::PickupRadius,
+            level: 2,
+        }),
+    );
+    assert_frame(
+        fixture!("upgrade_purchased"),
+        MessageData::UpgradePurchased(MsgUpgradePurchased {
+            kind: UpgradeKind::PickupRadius,
+            level: 2,
+            cost: 20.0,
+        }),
+    );
+}
+
+#[test]
+fn batch() {
+    assert_frame(
+        fixture!("batch"),
+        MessageData::Batch(vec![
+            MessageData::PlayerEat(30.25),
+            MessageData::PlayerDisconnected(8),
+        ]),
+    );
+}
+
+#[test]
+fn nested_batch_is_refused() {
+    let frame = MessageData::Batch(vec![MessageData::Batch(vec![])]).to_frame();
+    assert_eq!(
+        MessageData::from_frame(&frame),
+        Err(ProtocolError::NestedBatch)
+    );
+}
+
+/// Batch frame that holds another batch frame, `depth` levels deep.
+fn nested_batch(depth: usize) -> Vec<u8> {
+    let empty = MessageData::Batch(vec![]).to_frame();
+    // every level adds header, item count and item size.
+    let level = HEADER_SIZE + 8;
+    let mut frame = Vec::with_capacity(depth * level + empty.len());
+    for inner in (0..depth).rev() {
+        frame.extend(&empty[..HEADER_SIZE]);
+        frame.extend(&1u32.to_be_bytes());
+        frame.extend(&((inner * level + empty.len()) as u32).to_be_bytes());
+    }
+    frame.extend(&empty);
+    frame
+}
+
+#[test]
+fn deeply_nested_batch_is_refused_before_decoding() {
+    assert_eq!(
+        nested_batch(1),
+        MessageData::Batch(vec![MessageData::Batch(vec![])]).to_frame()
+    );
+    // would overflow stack (and copy megabytes) if items were decoded before being checked.
+    let frame = nested_batch(100_000);
+    assert_eq!(
+        MessageData::from_frame(&frame),
+        Err(ProtocolError::NestedBatch)
+    );
+}
+
+#[test]
+fn batch_items_are_checked_against_their_size() {
+    let item = MessageData::PlayerDisconnected(8).to_frame();
+    let batch = |size: u32, item: &[u8]| {
+        let mut frame = MessageData::Batch(vec![]).to_frame();
+        frame.truncate(HEADER_SIZE);
+        frame.extend(&1u32.to_be_bytes());
+        frame.extend(&size.to_be_bytes());
+        frame.extend(item);
+        frame
+    };
+    let size = item.len() as u32;
+    assert_eq!(
+        MessageData::from_frame(&batch(size, &item)),
+        Ok(MessageData::Batch(vec![MessageData::PlayerDisconnected(8)]))
+    );
+    assert_eq!(
+        MessageData::from_frame(&batch(size + 4, &item)),
+        Err(ProtocolError::Truncated)
+    );
+    let mut longer = item.clone();
+    longer.extend(&[0, 0]);
+    assert_eq!(
+        MessageData::from_frame(&batch(size + 2, &longer)),
+        Err(ProtocolError::TrailingBytes(2))
+    );
+    assert_eq!(
+        MessageData::from_frame(&batch(4, &item[..4])),
+        Err(ProtocolError::Truncated)
+    );
+    let mut other_version = item.clone();
+    other_version[7] = 1;
+    assert_eq!(
+        MessageData::from_frame(&batch(size, &other_version)),
+        Err(ProtocolError::UnknownVersion(1))
+    );
+}
+
+#[test]
+fn header_is_checked() {
+    let frame = fixture!("player_eat");
+    assert_eq!(
+        MessageData::from_frame(&frame[..HEADER_SIZE - 1]),
+        Err(ProtocolError::Truncated)
+    );
+    let mut frame = frame.to_vec();
+    frame[7] = 1;
+    assert_eq!(
+        MessageData::from_frame(&frame),
+        Err(ProtocolError::UnknownVersion(1))
+    );
+    assert_eq!(
+        MessageData::try_from((MessageID::new(5, 1), &frame[HEADER_SIZE..])),
+        Err(ProtocolError::UnknownVersion(1))
+    );
+    let mut frame = fixture!("player_eat").to_vec();
+    frame.push(0);
+    assert_eq!(
+        MessageData::from_frame(&frame),
+        Err(ProtocolError::TrailingBytes(1))
+    );
+}
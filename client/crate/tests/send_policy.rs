@@ -0,0 +1,67 @@
+use oxygengine::prelude::*;
+use your_time_is_currency::components::send_policy::{
+    AdaptiveSendPolicy, FixedSendPolicy, SendPolicy, SendSample,
+};
+
+const DELTA_TIME: f64 = 1.0 / 60.0;
+const STATE_SIZE: usize = 36;
+
+/// Runs policy for given time and returns how many states it sent.
+fn run(policy: &mut dyn SendPolicy, velocity: Vec2, time: f64) -> usize {
+    let sample = SendSample {
+        delta_time: DELTA_TIME,
+        velocity,
+    };
+    let mut sent = 0;
+    let mut elapsed = 0.0;
+    while elapsed < time {
+        if policy.update(&sample) {
+            policy.sent(STATE_SIZE);
+            sent += 1;
+        }
+        elapsed += DELTA_TIME;
+    }
+    sent
+}
+
+fn adaptive() -> AdaptiveSendPolicy {
+    AdaptiveSendPolicy::new(0.05, 0.25, 1.0, 10_000.0)
+}
+
+#[test]
+fn fixed_policy_sends_at_constant_rate() {
+    let mut policy = FixedSendPolicy::new(0.25);
+    assert_eq!(run(&mut policy, Vec2::zero(), 1.0), 4);
+    assert_eq!(run(&mut policy, Vec2::new(400.0, 0.0), 1.0), 4);
+}
+
+#[test]
+fn adaptive_policy_throttles_when_idle() {
+    let mut policy = adaptive();
+    run(&mut policy, Vec2::zero(), 0.1);
+    assert_eq!(run(&mut policy, Vec2::zero(), 5.0), 5);
+}
+
+#[test]
+fn adaptive_policy_sends_on_direction_change() {
+    let mut policy = adaptive();
+    run(&mut policy, Vec2::new(400.0, 0.0), 0.1);
+    assert_eq!(run(&mut policy, Vec2::new(400.0, 0.0), 0.1), 0);
+    assert_eq!(run(&mut policy, Vec2::new(0.0, 400.0), 0.1), 1);
+}
+
+#[test]
+fn adaptive_policy_caps_bandwidth() {
+    let mut policy = AdaptiveSendPolicy::new(0.0, 0.0, 0.0, STATE_SIZE as f64 * 10.0);
+    let sent = run(&mut policy, Vec2::new(400.0, 0.0), 10.0);
+    assert!(sent <= 10 * 10 + 10, "sent {} states", sent);
+    assert!(sent >= 10 * 10 - 10, "sent {} states", sent);
+}
+
+#[test]
+fn adaptive_policy_sends_after_reset() {
+    let mut policy = adaptive();
+    run(&mut policy, Vec2::zero(), 0.1);
+    policy.reset();
+    assert_eq!(run(&mut policy, Vec2::zero(), DELTA_TIME), 1);
+}
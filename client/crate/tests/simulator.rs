@@ -0,0 +1,104 @@
+use oxygengine::prelude::*;
+use your_time_is_currency::{
+    messages::{MessageData, MsgHello},
+    net::{
+        link::LinkConditions,
+        mock::{MockClient, MockHub, ScriptedServer},
+        simulator::{NetworkSimulator, SimulatedClient},
+    },
+};
+
+fn setup(url: &str, conditions: Option<LinkConditions>) -> (MockHub, SimulatedClient<MockClient>) {
+    let hub = MockHub::register(url, ScriptedServer::default(), LinkConditions::default());
+    if let Some(conditions) = conditions {
+        NetworkSimulator::register(url, conditions);
+    }
+    let client = SimulatedClient::<MockClient>::open(url).unwrap();
+    (hub, client)
+}
+
+fn send_hello(client: &mut SimulatedClient<MockClient>) {
+    let message = MessageData::Hello(MsgHello::new(None));
+    let id = MessageID::new(message.id(), 0);
+    let data: Vec<u8> = message.into();
+    client.send(id, &data);
+}
+
+/// Lets hub deliver frames sent by client and returns how many server got.
+fn server_received(hub: &MockHub, client: &mut SimulatedClient<MockClient>) -> usize {
+    client.process();
+    hub.advance(0.0);
+    hub.take_received().len()
+}
+
+#[test]
+fn frames_pass_through_without_simulator() {
+    let (hub, mut client) = setup("mock://sim-none", None);
+    assert_eq!(client.read_all().len(), 1);
+    send_hello(&mut client);
+    assert_eq!(server_received(&hub, &mut client), 1);
+}
+
+#[test]
+fn frames_are_delayed_both_ways() {
+    let url = "mock://sim-latency";
+    let (hub, mut client) = setup(
+        url,
+        Some(LinkConditions {
+            latency: 0.1,
+            ..Default::default()
+        }),
+    );
+    let simulator = NetworkSimulator::find(url).unwrap();
+    assert!(client.read_all().is_empty());
+    send_hello(&mut client);
+    assert_eq!(server_received(&hub, &mut client), 0);
+
+    simulator.advance(0.1);
+    assert_eq!(client.read_all().len(), 1);
+    assert_eq!(server_received(&hub, &mut client), 1);
+}
+
+#[test]
+fn frames_are_lost_or_duplicated() {
+    let (hub, mut client) = setup(
+        "mock://sim-loss",
+        Some(LinkConditions {
+            loss: 1.0,
+            ..Default::default()
+        }),
+    );
+    assert!(client.read_all().is_empty());
+    send_hello(&mut client);
+    assert_eq!(server_received(&hub, &mut client), 0);
+    assert_eq!(client.take_lost(), 2);
+    assert_eq!(client.take_lost(), 0);
+
+    let (hub, mut client) = setup(
+        "mock://sim-duplicate",
+        Some(LinkConditions {
+            duplicate: 1.0,
+            ..Default::default()
+        }),
+    );
+    assert_eq!(client.read_all().len(), 2);
+    send_hello(&mut client);
+    assert_eq!(server_received(&hub, &mut client), 2);
+}
+
+#[test]
+fn disabled_simulator_passes_frames_through() {
+    let url = "mock://sim-disabled";
+    let (hub, mut client) = setup(
+        url,
+        Some(LinkConditions {
+            latency: 1.0,
+            loss: 0.5,
+            ..Default::default()
+        }),
+    );
+    NetworkSimulator::find(url).unwrap().set_enabled(false);
+    assert_eq!(client.read_all().len(), 1);
+    send_hello(&mut client);
+    assert_eq!(server_received(&hub, &mut client), 1);
+}
@@ -0,0 +1,116 @@
+use oxygengine::prelude::*;
+use your_time_is_currency::{
+    components::{
+        collider::Collider, player_controller::PlayerController, snapshot_buffer::SnapshotBuffer,
+        speed::Speed, EnemyTag, PlayerTag,
+    },
+    headless::headless_world,
+    messages::MsgPlayerCorrection,
+    resources::{collisions::Collisions, network_clock::NetworkClock},
+    systems::{
+        collision::CollisionSystem, interpolation::InterpolationSystem,
+        reconciliation::ReconciliationSystem,
+    },
+};
+
+fn position(world: &World, entity: Entity) -> Vec2 {
+    world
+        .read_storage::<CompositeTransform>()
+        .get(entity)
+        .unwrap()
+        .get_translation()
+}
+
+#[test]
+fn interpolation_renders_between_snapshots() {
+    let mut world = headless_world(1.0 / 60.0);
+    let mut buffer = SnapshotBuffer::new(0.1, 0.25);
+    buffer.push(0.0, Vec2::new(0.0, 0.0), Vec2::zero());
+    buffer.push(0.2, Vec2::new(100.0, 0.0), Vec2::zero());
+    let entity = world
+        .create_entity()
+        .with(buffer)
+        .with(CompositeTransform::default())
+        .build();
+
+    world.write_resource::<NetworkClock>().advance(0.2);
+    InterpolationSystem.run_now(&world.res);
+    assert!((position(&world, entity).x - 50.0).abs() < 1.0e-3);
+}
+
+#[test]
+fn extrapolation_is_bounded() {
+    let mut world = headless_world(1.0 / 60.0);
+    let mut buffer = SnapshotBuffer::new(0.0, 0.25);
+    buffer.push(0.0, Vec2::new(0.0, 0.0), Vec2::new(100.0, 0.0));
+    let entity = world
+        .create_entity()
+        .with(buffer)
+        .with(CompositeTransform::default())
+        .build();
+
+    world.write_resource::<NetworkClock>().advance(10.0);
+    InterpolationSystem.run_now(&world.res);
+    assert!((position(&world, entity).x - 25.0).abs() < 1.0e-3);
+}
+
+#[test]
+fn reconciliation_replays_unacknowledged_inputs() {
+    let mut world = headless_world(1.0 / 60.0);
+    let mut controller = PlayerController::new(1);
+    controller.record(Vec2::new(1.0, 0.0), 0.5, 0, Vec2::new(50.0, 0.0));
+    controller.record(Vec2::new(1.0, 0.0), 0.5, 0, Vec2::new(100.0, 0.0));
+    controller.correct(MsgPlayerCorrection {
+        sequence: 1,
+        position: Vec2::new(0.0, 10.0),
+        velocity: Vec2::new(100.0, 0.0),
+    });
+    let entity = world
+        .create_entity()
+        .with(controller)
+        .with(Speed(100.0))
+        .with(PlayerTag)
+        .with(CompositeTransform::translation(Vec2::new(100.0, 0.0)))
+        .build();
+
+    ReconciliationSystem.run_now(&world.res);
+    let position = position(&world, entity);
+    assert!((position.x - 50.0).abs() < 1.0e-3);
+    assert!((position.y - 10.0).abs() < 1.0e-3);
+}
+
+#[test]
+fn collision_pairs_player_with_overlapping_enemies() {
+    let mut world = headless_world(1.0 / 60.0);
+    let player = world
+        .create_entity()
+        .with(CompositeTransform::translation(Vec2::new(0.0, 0.0)))
+        .with(Collider(87.5))
+        .with(PlayerTag)
+        .build();
+    let near = world
+        .create_entity()
+        .with(CompositeTransform::translation(Vec2::new(170.0, 0.0)))
+        .with(Collider(87.5))
+        .with(EnemyTag)
+        .build();
+    world
+        .create_entity()
+        .with(CompositeTransform::translation(Vec2::new(180.0, 0.0)))
+        .with(Collider(87.5))
+        .with(EnemyTag)
+        .build();
+    world
+        .create_entity()
+        .with(CompositeTransform::translation(Vec2::new(10.0, 0.0)))
+        .with(EnemyTag)
+        .build();
+
+    CollisionSystem.run_now(&world.res);
+    let collisions = world.read_resource::<Collisions>();
+    assert_eq!(collisions.events().len(), 1);
+    let event = collisions.events()[0];
+    assert_eq!(event.player, player);
+    assert_eq!(event.enemy, near);
+    assert!((event.depth - 5.0).abs() < 1.0e-3);
+}
@@ -0,0 +1,46 @@
+use your_time_is_currency::components::time_decay::{DecayCurve, TimeDecay};
+
+#[test]
+fn default_decay_loses_one_second_per_second() {
+    let mut decay = TimeDecay::default();
+    assert_eq!(decay.curve, DecayCurve::Constant(1.0));
+    assert_eq!(decay.update(0.5, 60.0, true), 0.5);
+    assert_eq!(decay.update(0.5, 1.0, false), 0.5);
+}
+
+#[test]
+fn constant_decay_ignores_time_and_movement() {
+    let mut decay = TimeDecay::new(DecayCurve::Constant(2.0));
+    assert_eq!(decay.rate(10.0, false), 2.0);
+    assert_eq!(decay.rate(100.0, true), 2.0);
+    assert_eq!(decay.update(0.25, 100.0, true), 0.5);
+}
+
+#[test]
+fn proportional_decay_bleeds_rich_players_faster() {
+    let decay = TimeDecay::new(DecayCurve::Proportional(0.01));
+    assert_eq!(decay.rate(0.0, false), 1.0);
+    assert_eq!(decay.rate(100.0, false), 2.0);
+    assert_eq!(decay.rate(300.0, false), 4.0);
+    assert!(decay.rate(60.0, false) < decay.rate(120.0, false));
+}
+
+#[test]
+fn accelerating_decay_grows_over_match() {
+    let mut decay = TimeDecay::new(DecayCurve::Accelerating(0.1));
+    assert_eq!(decay.rate(60.0, false), 1.0);
+    // rate of step is taken before it moves match forward.
+    assert_eq!(decay.update(10.0, 60.0, false), 10.0);
+    assert_eq!(decay.elapsed(), 10.0);
+    assert_eq!(decay.rate(60.0, false), 2.0);
+    assert_eq!(decay.update(1.0, 60.0, false), 2.0);
+}
+
+#[test]
+fn moving_decay_is_faster_only_while_moving() {
+    let mut decay = TimeDecay::new(DecayCurve::Moving(1.5));
+    assert_eq!(decay.rate(60.0, false), 1.0);
+    assert_eq!(decay.rate(60.0, true), 1.5);
+    assert_eq!(decay.update(2.0, 60.0, true), 3.0);
+    assert_eq!(decay.update(2.0, 60.0, false), 2.0);
+}
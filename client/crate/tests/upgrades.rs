@@ -0,0 +1,62 @@
+use your_time_is_currency::{
+    components::upgrades::Upgrades,
+    consts::{MAX_UPGRADE_LEVEL, UPGRADE_BASE_COST},
+    messages::UpgradeKind,
+    resources::shop::Shop,
+};
+
+#[test]
+fn upgrade_levels_get_more_expensive_until_max() {
+    let mut upgrades = Upgrades::default();
+    assert_eq!(upgrades.next_level(UpgradeKind::Speed), Some(1));
+    assert_eq!(Upgrades::cost(1), UPGRADE_BASE_COST);
+    assert_eq!(Upgrades::cost(2), UPGRADE_BASE_COST * 2.0);
+
+    upgrades.set_level(UpgradeKind::Speed, MAX_UPGRADE_LEVEL);
+    assert_eq!(upgrades.next_level(UpgradeKind::Speed), None);
+    assert!(!upgrades.can_buy(UpgradeKind::Speed, 1000.0));
+    upgrades.set_level(UpgradeKind::Speed, MAX_UPGRADE_LEVEL + 1);
+    assert_eq!(upgrades.level(UpgradeKind::Speed), MAX_UPGRADE_LEVEL);
+    assert_eq!(upgrades.level(UpgradeKind::DecayRate), 0);
+}
+
+#[test]
+fn upgrade_cannot_cost_whole_time() {
+    let upgrades = Upgrades::default();
+    let cost = Upgrades::cost(1);
+    assert!(!upgrades.can_buy(UpgradeKind::PickupRadius, cost));
+    assert!(upgrades.can_buy(UpgradeKind::PickupRadius, cost + 0.5));
+}
+
+#[test]
+fn upgrades_change_modifiers() {
+    let mut upgrades = Upgrades::default();
+    assert_eq!(upgrades.speed_multiplier(), 1.0);
+    assert_eq!(upgrades.pickup_radius_bonus(), 0.0);
+    assert_eq!(upgrades.decay_multiplier(), 1.0);
+
+    upgrades.set_level(UpgradeKind::Speed, 2);
+    upgrades.set_level(UpgradeKind::PickupRadius, 1);
+    upgrades.set_level(UpgradeKind::DecayRate, 3);
+    assert!(upgrades.speed_multiplier() > 1.0);
+    assert!(upgrades.pickup_radius_bonus() > 0.0);
+    assert!(upgrades.decay_multiplier() < 1.0);
+    assert!(upgrades.decay_multiplier() > 0.0);
+}
+
+#[test]
+fn shop_takes_requests_only_while_open() {
+    let mut shop = Shop::default();
+    shop.request(UpgradeKind::Speed);
+    assert!(shop.drain_requests().is_empty());
+
+    shop.toggle();
+    shop.request(UpgradeKind::Speed);
+    shop.request(UpgradeKind::Speed);
+    shop.request(UpgradeKind::DecayRate);
+    assert_eq!(
+        shop.drain_requests(),
+        vec![UpgradeKind::Speed, UpgradeKind::DecayRate]
+    );
+    assert!(shop.drain_requests().is_empty());
+}
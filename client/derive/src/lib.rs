@@ -0,0 +1,130 @@
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, Ident};
+
+/// Generates big-endian `Message` read/write implementation.
+/// Struct fields are stored in declaration order, enum variants are prefixed
+/// with their `u32` index followed by variant fields.
+#[proc_macro_derive(Message)]
+pub fn derive_message(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let (read, write) = match &input.data {
+        Data::Struct(data) => {
+            let read = read_fields(quote! { Self }, &data.fields);
+            let (pattern, write) = write_fields(&data.fields);
+            let write = quote! {
+                let Self #pattern = self;
+                #write
+            };
+            (quote! { Ok(#read) }, write)
+        }
+        Data::Enum(data) => {
+            let mut read_arms = vec![];
+            let mut write_arms = vec![];
+            for (index, variant) in data.variants.iter().enumerate() {
+                let index = index as u32;
+                let ident = &variant.ident;
+                let read = read_fields(quote! { Self::#ident }, &variant.fields);
+                let (pattern, write) = write_fields(&variant.fields);
+                read_arms.push(quote! { #index => Ok(#read), });
+                write_arms.push(quote! {
+                    Self::#ident #pattern => {
+                        crate::messages::Message::msg_write(&#index, stream);
+                        #write
+                    }
+                });
+            }
+            let read = quote! {
+                match <u32 as crate::messages::Message>::msg_read(stream)? {
+                    #( #read_arms )*
+                    index => Err(crate::messages::ProtocolError::UnknownVariant(index)),
+                }
+            };
+            let write = quote! {
+                match self {
+                    #( #write_arms )*
+                }
+            };
+            (read, write)
+        }
+        Data::Union(_) => {
+            return syn::Error::new(Span::call_site(), "`Message` cannot be derived for unions")
+                .to_compile_error()
+                .into();
+        }
+    };
+
+    let expanded = quote! {
+        impl #impl_generics crate::messages::Message for #name #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            fn msg_read<R>(stream: &mut R) -> Result<Self, crate::messages::ProtocolError>
+            where
+                R: std::io::Read,
+            {
+                #read
+            }
+
+            #[allow(unused_variables)]
+            fn msg_write<W>(&self, stream: &mut W)
+            where
+                W: std::io::Write,
+            {
+                #write
+            }
+        }
+    };
+    expanded.into()
+}
+
+fn read_fields(path: TokenStream2, fields: &Fields) -> TokenStream2 {
+    match fields {
+        Fields::Named(fields) => {
+            let fields = fields.named.iter().map(|field| {
+                let ident = &field.ident;
+                quote! { #ident: crate::messages::Message::msg_read(stream)? }
+            });
+            quote! { #path { #( #fields ),* } }
+        }
+        Fields::Unnamed(fields) => {
+            let fields = fields
+                .unnamed
+                .iter()
+                .map(|_| quote! { crate::messages::Message::msg_read(stream)? });
+            quote! { #path ( #( #fields ),* ) }
+        }
+        Fields::Unit => path,
+    }
+}
+
+/// Returns destructuring pattern of fields and code writing them.
+fn write_fields(fields: &Fields) -> (TokenStream2, TokenStream2) {
+    match fields {
+        Fields::Named(fields) => {
+            let idents = fields
+                .named
+                .iter()
+                .map(|field| field.ident.clone().unwrap())
+                .collect::<Vec<_>>();
+            (
+                quote! { { #( #idents ),* } },
+                quote! { #( crate::messages::Message::msg_write(#idents, stream); )* },
+            )
+        }
+        Fields::Unnamed(fields) => {
+            let idents = (0..fields.unnamed.len())
+                .map(|index| Ident::new(&format!("field{}", index), Span::call_site()))
+                .collect::<Vec<_>>();
+            (
+                quote! { ( #( #idents ),* ) },
+                quote! { #( crate::messages::Message::msg_write(#idents, stream); )* },
+            )
+        }
+        Fields::Unit => (quote! {}, quote! {}),
+    }
+}
@@ -0,0 +1,122 @@
+#!/usr/bin/env node
+
+// Writes golden frames used by client protocol tests (`client/crate/tests/protocol.rs`).
+// Regenerate with `npm run fixtures` whenever wire format changes on purpose.
+
+const fs = require('fs'),
+  path = require('path'),
+  {
+    ID,
+    MSG_INIT_PLAYER,
+    MSG_NEW_PLAYER,
+    MSG_PLAYER_STATE,
+    MSG_PLAYER_DISCONNECTED,
+    MSG_PLAYER_EAT,
+    MSG_HELLO,
+    MSG_WELCOME,
+    MSG_PLAYER_INPUT,
+    MSG_PLAYER_CORRECTION,
+    MSG_PING,
+    MSG_PONG,
+    MSG_PLAYER_STATE_ACK,
+    MSG_VIEW_AREA,
+    MSG_PICKUP_SPAWNED,
+    MSG_PICKUP_DESPAWNED,
+    MSG_COLLECT_PICKUP,
+    MSG_PURCHASE_UPGRADE,
+    MSG_UPGRADE_PURCHASED,
+    FLAG_DASH,
+    UPGRADE_PICKUP_RADIUS,
+    writeBatch,
+    writeCompactState,
+    writeByte,
+    writeShort,
+    writeDouble,
+    writeInteger,
+    writeMessage,
+  } = require('./protocol'),
+  output = path.join(__dirname, '..', 'client', 'crate', 'tests', 'fixtures');
+
+const fixtures = {
+  init_player: writeMessage(
+    MSG_INIT_PLAYER,
+    [new ID(7), 'Ada', 59.5, 128.25, -64.5, 'a1b2c3']
+  ),
+  new_player: writeMessage(
+    MSG_NEW_PLAYER,
+    [new ID(8), 'Grace', 30.25, -512, 256.75]
+  ),
+  player_state: writeMessage(
+    MSG_PLAYER_STATE,
+    [
+      new ID(7), 42.5, 10.5, -20.25, 400, -0.5, writeDouble(12.375),
+      writeByte(FLAG_DASH),
+    ]
+  ),
+  player_disconnected: writeMessage(MSG_PLAYER_DISCONNECTED, [new ID(8)]),
+  player_eat: writeMessage(MSG_PLAYER_EAT, [30.25]),
+  hello: writeMessage(
+    MSG_HELLO,
+    [writeInteger(2), '0.1.0', writeInteger(0), writeInteger(1), 'a1b2c3']
+  ),
+  hello_without_token: writeMessage(
+    MSG_HELLO,
+    [writeInteger(2), '0.1.0', writeInteger(0), writeInteger(0)]
+  ),
+  welcome: writeMessage(MSG_WELCOME, [writeInteger(2), writeInteger(0)]),
+  player_input: writeMessage(
+    MSG_PLAYER_INPUT,
+    [writeInteger(3), 1, -1, 0.015625, writeByte(FLAG_DASH)]
+  ),
+  player_correction: writeMessage(
+    MSG_PLAYER_CORRECTION,
+    [writeInteger(3), 100.5, -50.25, 400, -400]
+  ),
+  ping: writeMessage(MSG_PING, [writeDouble(1.5)]),
+  pong: writeMessage(MSG_PONG, [writeDouble(1.5), writeDouble(1234.125)]),
+  player_state_compact: writeCompactState({
+    id: 7,
+    sequence: 5,
+    baseline: 0,
+    timestamp: 12375,
+    time: 425,
+    cell: [0, -1],
+    offset: [672, 63872],
+    velocity: [192, 6400],
+    flags: 0,
+  }),
+  player_state_compact_delta: writeCompactState({
+    id: 7,
+    sequence: 6,
+    baseline: 5,
+    timestamp: 12625,
+    time: null,
+    cell: null,
+    offset: [700, 63872],
+    velocity: null,
+    flags: FLAG_DASH,
+  }),
+  player_state_ack: writeMessage(MSG_PLAYER_STATE_ACK, [writeShort(5)]),
+  view_area: writeMessage(MSG_VIEW_AREA, [100.5, -200.25, 640, 360]),
+  pickup_spawned: writeMessage(MSG_PICKUP_SPAWNED, [new ID(3), 10, 128.5, -64.25]),
+  pickup_despawned: writeMessage(MSG_PICKUP_DESPAWNED, [new ID(3)]),
+  collect_pickup: writeMessage(MSG_COLLECT_PICKUP, [new ID(3)]),
+  purchase_upgrade: writeMessage(
+    MSG_PURCHASE_UPGRADE,
+    [writeInteger(UPGRADE_PICKUP_RADIUS), writeByte(2)]
+  ),
+  upgrade_purchased: writeMessage(
+    MSG_UPGRADE_PURCHASED,
+    [writeInteger(UPGRADE_PICKUP_RADIUS), writeByte(2), 20]
+  ),
+  batch: writeBatch([
+    writeMessage(MSG_PLAYER_EAT, [30.25]),
+    writeMessage(MSG_PLAYER_DISCONNECTED, [new ID(8)]),
+  ]),
+};
+
+fs.mkdirSync(output, { recursive: true });
+for (const name in fixtures) {
+  fs.writeFileSync(path.join(output, name + '.bin'), fixtures[name]);
+  console.log('* Written: ' + name + '.bin');
+}
@@ -1,28 +1,87 @@
 #!/usr/bin/env node
 
 const ws = require('ws'),
+  crypto = require('crypto'),
   namegen = require('node-random-name'),
+  {
+    ID,
+    version,
+    MSG_INIT_PLAYER,
+    MSG_NEW_PLAYER,
+    MSG_PLAYER_STATE,
+    MSG_PLAYER_DISCONNECTED,
+    MSG_PLAYER_EAT,
+    MSG_HELLO,
+    MSG_WELCOME,
+    MSG_PLAYER_INPUT,
+    MSG_PLAYER_CORRECTION,
+    MSG_PING,
+    MSG_PONG,
+    MSG_PLAYER_STATE_COMPACT,
+    MSG_PLAYER_STATE_ACK,
+    MSG_VIEW_AREA,
+    MSG_PICKUP_SPAWNED,
+    MSG_PICKUP_DESPAWNED,
+    MSG_COLLECT_PICKUP,
+    MSG_PURCHASE_UPGRADE,
+    MSG_UPGRADE_PURCHASED,
+    CAPABILITY_BATCH,
+    CAPABILITY_COMPACT_STATE,
+    FLAG_DASH,
+    UPGRADE_SPEED,
+    UPGRADE_PICKUP_RADIUS,
+    UPGRADE_DECAY_RATE,
+    maxBatchSize,
+    writeDouble,
+    writeInteger,
+    writeByte,
+    writeShort,
+    writeMessage,
+    writeBatch,
+    readCompactState,
+    resolveCompactState,
+    writeCompactState,
+    compactToState,
+  } = require('./protocol'),
   port = 8090,
-  version = 0,
+  protocolVersion = 3,
+  capabilities = CAPABILITY_BATCH | CAPABILITY_COMPACT_STATE,
+  maxCompactBaselines = 32,
   server = new ws.Server({ port }),
   clients = [],
+  sessions = {},
+  pickups = {},
+  maxPickups = 32,
+  pickupRange = 1500,
+  pickupTimes = [5, 10, 15],
+  pickupLifetime = 30000,
+  // distance between player and pickup centers that still counts as touching (with lag).
+  pickupReach = 150,
+  resumeGrace = 15000,
+  // players are sent this far outside of view area, clients forget them only beyond it.
+  viewMargin = 256,
   spawnRange = 400,
   initLifetime = 60,
-  MSG_INIT_PLAYER = 1,
-  MSG_NEW_PLAYER = 2,
-  MSG_PLAYER_STATE = 3,
-  MSG_PLAYER_DISCONNECTED = 4,
-  MSG_PLAYER_EAT = 5;
+  playerSpeed = 400,
+  maxInputDelta = 0.1,
+  dashCost = 5,
+  dashDuration = 0.25,
+  dashMultiplier = 3,
+  dashCooldown = 2000,
+  // cooldown is checked against arrival of inputs, which jitter makes come closer.
+  dashCooldownSlack = 250,
+  minDashTime = 10,
+  // upgrade level costs this many times base cost.
+  upgradeBaseCost = 10,
+  maxUpgradeLevel = 3,
+  upgradeSpeedStep = 0.15,
+  upgradePickupRadiusStep = 50,
+  startTime = Date.now();
 var gid = 1;
+var pickupId = 1;
 
 console.log('* Listening on port: ' + port);
 
-class ID {
-  constructor(id) {
-    this.id = id;
-  }
-}
-
 server.on('connection', function(socket) {
   const pos = findInitPosition();
   const client = {
@@ -31,8 +90,27 @@ server.on('connection', function(socket) {
     name: namegen(),
     time: initLifetime,
     alive: true,
+    joined: false,
+    killed: false,
+    token: crypto.randomBytes(16).toString('hex'),
+    capabilities: 0,
+    queue: [],
+    // area client wants players from, null means everywhere.
+    view: null,
+    // received compact states by sequence, client may send deltas against them.
+    states: {},
+    stateSequences: [],
     x: pos[0],
     y: pos[1],
+    vx: 0,
+    vy: 0,
+    sequence: 0,
+    corrected: true,
+    // input time left of current dash.
+    dash: 0,
+    dashReadyAt: 0,
+    // levels by upgrade kind, decay rate only matters to client.
+    upgrades: [0, 0, 0],
   };
   socket.on('error', console.error);
   socket.on('close', function() {
@@ -44,8 +122,15 @@ server.on('connection', function(socket) {
     client.intervalNew = null;
     clearInterval(client.intervalCollision);
     client.intervalCollision = null;
-    sendAll(writeMessage(MSG_PLAYER_DISCONNECTED, [client.id]));
-    clients.splice(clients.indexOf(client), 1);
+    clearInterval(client.intervalCorrection);
+    client.intervalCorrection = null;
+    if (client.joined) {
+      sendAll(writeMessage(MSG_PLAYER_DISCONNECTED, [client.id]));
+      clients.splice(clients.indexOf(client), 1);
+      if (!client.killed && client.time > 0) {
+        keepSession(client);
+      }
+    }
   });
   socket.on('message', function(msg) {
     if (msg instanceof Buffer) {
@@ -53,16 +138,41 @@ server.on('connection', function(socket) {
         const ver = msg.readUInt32BE(4);
         if (ver === version) {
           const mid = msg.readUInt32BE(0);
-          if (mid === MSG_PLAYER_STATE) {
+          if (mid === MSG_HELLO) {
+            onHello(client, msg);
+          } else if (mid === MSG_PING) {
+            send(client, writeMessage(
+              MSG_PONG,
+              [msg.slice(8, 16), writeDouble(serverTime())]
+            ));
+          } else if (mid === MSG_PLAYER_INPUT && client.joined) {
+            onPlayerInput(client, msg);
+          } else if (mid === MSG_PLAYER_STATE && client.joined) {
             client.alive = true;
             client.time = msg.readFloatBE(12);
-            client.x = msg.readFloatBE(16);
-            client.y = msg.readFloatBE(20);
+            // once client sends inputs, its position is simulated here.
+            if (client.sequence === 0) {
+              client.x = msg.readFloatBE(16);
+              client.y = msg.readFloatBE(20);
+            }
             if (client.time <= 0) {
-              killClient(client);
+              killPlayer(client);
             } else {
-              sendAll(msg, client);
+              sendNearby(msg, client);
             }
+          } else if (mid === MSG_PLAYER_STATE_COMPACT && client.joined) {
+            onPlayerStateCompact(client, msg);
+          } else if (mid === MSG_COLLECT_PICKUP && client.joined) {
+            collectPickup(client, msg.readUInt32BE(8));
+          } else if (mid === MSG_PURCHASE_UPGRADE && client.joined) {
+            purchaseUpgrade(client, msg.readUInt32BE(8), msg.readUInt8(12));
+          } else if (mid === MSG_VIEW_AREA) {
+            client.view = {
+              x: msg.readFloatBE(8),
+              y: msg.readFloatBE(12),
+              hw: msg.readFloatBE(16),
+              hh: msg.readFloatBE(20),
+            };
           }
         }
       } catch (err) {
@@ -71,32 +181,179 @@ server.on('connection', function(socket) {
     }
   });
 
+  send(client, writeMessage(
+    MSG_WELCOME,
+    [writeInteger(protocolVersion), writeInteger(capabilities)]
+  ));
+  console.log('* Client connected: ' + client.name);
+});
+
+function onHello(client, msg) {
+  if (client.joined) {
+    return;
+  }
+  const clientVersion = msg.readUInt32BE(8);
+  const buildSize = msg.readUInt32BE(12);
+  const build = msg.toString('utf8', 16, 16 + buildSize);
+  const clientCapabilities = msg.readUInt32BE(16 + buildSize);
+  var token = null;
+  if (msg.readUInt32BE(20 + buildSize) === 1) {
+    const tokenSize = msg.readUInt32BE(24 + buildSize);
+    token = msg.toString('utf8', 28 + buildSize, 28 + buildSize + tokenSize);
+  }
+  if (clientVersion !== protocolVersion) {
+    console.log(
+      '* Client rejected: ' + client.name +
+      ' (protocol: ' + clientVersion + ', build: ' + build + ')'
+    );
+    killPlayer(client);
+    return;
+  }
+  client.capabilities = clientCapabilities & capabilities;
+  if (token !== null) {
+    resumeSession(client, token);
+  }
+  joinClient(client);
+}
+
+function keepSession(client) {
+  const session = {
+    id: client.id,
+    name: client.name,
+    time: client.time,
+    x: client.x,
+    y: client.y,
+    upgrades: client.upgrades,
+  };
+  session.timeout = setTimeout(function() {
+    delete sessions[client.token];
+  }, resumeGrace);
+  sessions[client.token] = session;
+}
+
+function resumeSession(client, token) {
+  const session = sessions[token];
+  if (!session) {
+    return;
+  }
+  clearTimeout(session.timeout);
+  delete sessions[token];
+  client.id = session.id;
+  client.name = session.name;
+  client.time = session.time;
+  client.x = session.x;
+  client.y = session.y;
+  client.upgrades = session.upgrades;
+  console.log('* Client resumed: ' + client.name);
+}
+
+function onPlayerInput(client, msg) {
+  const sequence = msg.readUInt32BE(8);
+  if (sequence <= client.sequence) {
+    return;
+  }
+  const dx = msg.readFloatBE(12);
+  const dy = msg.readFloatBE(16);
+  const dt = Math.min(Math.max(msg.readFloatBE(20), 0), maxInputDelta);
+  const dashing = (msg.readUInt8(24) & FLAG_DASH) !== 0;
+  const now = Date.now();
+  if (!dashing) {
+    client.dash = 0;
+  }
+  // time itself is reported by client, here we only refuse dashes it cannot afford.
+  if (
+    dashing &&
+    client.dash <= 0 &&
+    now >= client.dashReadyAt &&
+    client.time - dashCost >= minDashTime
+  ) {
+    client.time -= dashCost;
+    client.dash = dashDuration;
+    client.dashReadyAt = now + dashCooldown - dashCooldownSlack;
+  }
+  const speed = playerSpeed * (1 + upgradeSpeedStep * client.upgrades[UPGRADE_SPEED]) *
+    (dashing && client.dash > 0 ? dashMultiplier : 1);
+  client.sequence = sequence;
+  client.vx = dx * speed;
+  client.vy = dy * speed;
+  client.x += client.vx * dt;
+  client.y += client.vy * dt;
+  client.dash -= dt;
+  client.corrected = false;
+}
+
+function onPlayerStateCompact(client, msg) {
+  const compact = resolveCompactState(
+    readCompactState(msg),
+    client.states[msg.readUInt16BE(14)]
+  );
+  if (compact === null) {
+    return;
+  }
+  client.states[compact.sequence] = compact;
+  client.stateSequences.push(compact.sequence);
+  if (client.stateSequences.length > maxCompactBaselines) {
+    delete client.states[client.stateSequences.shift()];
+  }
+  send(client, writeMessage(MSG_PLAYER_STATE_ACK, [writeShort(compact.sequence)]));
+
+  const state = compactToState(compact);
+  client.alive = true;
+  client.time = state.time;
+  // once client sends inputs, its position is simulated here.
+  if (client.sequence === 0) {
+    client.x = state.x;
+    client.y = state.y;
+  }
+  if (client.time <= 0) {
+    killPlayer(client);
+    return;
+  }
+  // other clients get absolute state, in format they understand.
+  const compactMsg = writeCompactState(compact);
+  const fullMsg = writeMessage(MSG_PLAYER_STATE, [
+    client.id, state.time, state.x, state.y, state.vx, state.vy,
+    writeDouble(state.timestamp), writeByte(state.flags),
+  ]);
+  for (var i = 0; i < clients.length; ++i) {
+    const other = clients[i];
+    if (other !== client && sees(other, client)) {
+      send(other, other.capabilities & CAPABILITY_COMPACT_STATE ? compactMsg : fullMsg);
+    }
+  }
+}
+
+function joinClient(client) {
+  client.joined = true;
   send(client, writeMessage(
     MSG_INIT_PLAYER,
-    [client.id, client.name, client.time, client.x, client.y]
+    [client.id, client.name, client.time, client.x, client.y, client.token]
   ));
   clients.push(client);
+  for (const id in pickups) {
+    send(client, writePickup(pickups[id]));
+  }
 
   client.timeoutInit = setTimeout(function() {
     for (var i = 0; i < clients.length; ++i) {
       const other = clients[i];
-      if (other !== client) {
+      if (other !== client && sees(client, other)) {
         send(client, writeMessage(
           MSG_NEW_PLAYER,
           [other.id, other.name, other.time, other.x, other.y]
         ));
       }
     }
-    sendAll(writeMessage(
+    sendNearby(writeMessage(
       MSG_NEW_PLAYER,
       [client.id, client.name, client.time, client.x, client.y]
     ), client);
   }, 1000);
-  console.log('* Client connected: ' + client.name);
+  console.log('* Client joined: ' + client.name);
 
   client.intervalNew = setInterval(function() {
     if (client.alive && client.time > 0) {
-      sendAll(writeMessage(
+      sendNearby(writeMessage(
         MSG_NEW_PLAYER,
         [client.id, client.name, client.time, client.x, client.y]
       ), client);
@@ -120,7 +377,87 @@ server.on('connection', function(socket) {
       }
     }
   }, 100);
-});
+
+  client.intervalCorrection = setInterval(function() {
+    if (client.alive && !client.corrected) {
+      client.corrected = true;
+      send(client, writeMessage(
+        MSG_PLAYER_CORRECTION,
+        [writeInteger(client.sequence), client.x, client.y, client.vx, client.vy]
+      ));
+    }
+  }, 100);
+}
+
+setInterval(function() {
+  const now = Date.now();
+  for (const id in pickups) {
+    if (pickups[id].expires <= now) {
+      despawnPickup(pickups[id]);
+    }
+  }
+  if (clients.length > 0 && Object.keys(pickups).length < maxPickups) {
+    const nearby = clients[(Math.random() * clients.length | 0) % clients.length];
+    const dir = Math.random() * Math.PI * 2;
+    const distance = Math.random() * pickupRange;
+    const pickup = {
+      id: new ID(pickupId++),
+      time: pickupTimes[(Math.random() * pickupTimes.length | 0) % pickupTimes.length],
+      x: nearby.x + Math.cos(dir) * distance,
+      y: nearby.y + Math.sin(dir) * distance,
+      expires: now + pickupLifetime,
+    };
+    pickups[pickup.id.id] = pickup;
+    sendAll(writePickup(pickup));
+  }
+}, 1000);
+
+function writePickup(pickup) {
+  return writeMessage(MSG_PICKUP_SPAWNED, [pickup.id, pickup.time, pickup.x, pickup.y]);
+}
+
+function despawnPickup(pickup) {
+  delete pickups[pickup.id.id];
+  sendAll(writeMessage(MSG_PICKUP_DESPAWNED, [pickup.id]));
+}
+
+function collectPickup(client, id) {
+  const pickup = pickups[id];
+  if (!pickup || !client.alive || client.time <= 0) {
+    return;
+  }
+  const dx = client.x - pickup.x;
+  const dy = client.y - pickup.y;
+  const reach = pickupReach +
+    upgradePickupRadiusStep * client.upgrades[UPGRADE_PICKUP_RADIUS];
+  if (dx * dx + dy * dy > reach * reach) {
+    return;
+  }
+  despawnPickup(pickup);
+  client.time += pickup.time;
+  send(client, writeMessage(MSG_PLAYER_EAT, [pickup.time]));
+}
+
+// level is the one being bought, so repeated request does not buy it twice.
+function purchaseUpgrade(client, kind, level) {
+  if (kind > UPGRADE_DECAY_RATE || !client.alive) {
+    return;
+  }
+  const cost = upgradeBaseCost * level;
+  if (
+    level !== client.upgrades[kind] + 1 ||
+    level > maxUpgradeLevel ||
+    client.time <= cost
+  ) {
+    return;
+  }
+  client.time -= cost;
+  client.upgrades[kind] = level;
+  send(client, writeMessage(
+    MSG_UPGRADE_PURCHASED,
+    [writeInteger(kind), writeByte(level), cost]
+  ));
+}
 
 // setInterval(function() {
 //   console.log('CLIENTS: ' + clients.length + ' | ' + clients.map(function(c) {
@@ -154,7 +491,12 @@ function playersCollide(client, other) {
   return dx * dx + dy * dy <= 10000; // 100 * 100
 }
 
+function serverTime() {
+  return (Date.now() - startTime) / 1000;
+}
+
 function killPlayer(client) {
+  client.killed = true;
   try {
     client.socket.terminate();
   } catch (err) {
@@ -162,54 +504,30 @@ function killPlayer(client) {
   }
 }
 
-function writeHeader(id) {
-  const buffer = Buffer.alloc(8);
-  buffer.writeUInt32BE(id, 0);
-  buffer.writeUInt32BE(version, 4);
-  return buffer;
-}
-
-function writeID(value) {
-  const buffer = Buffer.alloc(4);
-  buffer.writeUInt32BE(value.id, 0);
-  return buffer;
-}
-
-function writeNumber(value) {
-  const buffer = Buffer.alloc(4);
-  buffer.writeFloatBE(value, 0);
-  return buffer;
-}
-
-function writeInteger(value) {
-  const buffer = Buffer.alloc(4);
-  buffer.writeUInt32BE(value, 0);
-  return buffer;
-}
-
-function writeString(value) {
-  const buffer = Buffer.from(value);
-  return Buffer.concat([writeInteger(buffer.length), buffer]);
+function send(client, msg) {
+  if (client.capabilities & CAPABILITY_BATCH) {
+    // messages sent during this event loop turn go out together.
+    if (client.queue.length === 0) {
+      setImmediate(function() {
+        flush(client);
+      });
+    }
+    client.queue.push(msg);
+  } else {
+    sendFrame(client, msg);
+  }
 }
 
-function writeMessage(id, data) {
-  const parts = [writeHeader(id)];
-  for (var i = 0; i < data.length; ++i) {
-    const item = data[i];
-    if (item instanceof Buffer) {
-      parts.push(item);
-    } else if (item instanceof ID) {
-      parts.push(writeID(item));
-    } else if (typeof item === 'number') {
-      parts.push(writeNumber(item));
-    } else if (typeof item === 'string') {
-      parts.push(writeString(item));
-    }
+function flush(client) {
+  const queue = client.queue;
+  client.queue = [];
+  for (var i = 0; i < queue.length; i += maxBatchSize) {
+    const frames = queue.slice(i, i + maxBatchSize);
+    sendFrame(client, frames.length === 1 ? frames[0] : writeBatch(frames));
   }
-  return Buffer.concat(parts);
 }
 
-function send(client, msg) {
+function sendFrame(client, msg) {
   try {
     if (client.alive && client.socket.readyState == 1) {
       client.socket.send(msg);
@@ -227,3 +545,21 @@ function sendAll(msg, exclude) {
     }
   }
 }
+
+function sees(client, other) {
+  const view = client.view;
+  return view === null || (
+    Math.abs(other.x - view.x) <= view.hw + viewMargin &&
+    Math.abs(other.y - view.y) <= view.hh + viewMargin
+  );
+}
+
+// Sends message about player to other clients that have this player in their view area.
+function sendNearby(msg, player) {
+  for (var i = 0; i < clients.length; ++i) {
+    const client = clients[i];
+    if (client !== player && sees(client, player)) {
+      send(client, msg);
+    }
+  }
+}
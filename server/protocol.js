@@ -0,0 +1,281 @@
+// Wire format shared by server and client: every frame starts with 8 byte header
+// (message id and version as big-endian u32) followed by message fields.
+
+const version = 0,
+  MSG_INIT_PLAYER = 1,
+  MSG_NEW_PLAYER = 2,
+  MSG_PLAYER_STATE = 3,
+  MSG_PLAYER_DISCONNECTED = 4,
+  MSG_PLAYER_EAT = 5,
+  MSG_HELLO = 6,
+  MSG_WELCOME = 7,
+  MSG_PLAYER_INPUT = 8,
+  MSG_PLAYER_CORRECTION = 9,
+  MSG_PING = 10,
+  MSG_PONG = 11,
+  MSG_BATCH = 12,
+  MSG_PLAYER_STATE_COMPACT = 13,
+  MSG_PLAYER_STATE_ACK = 14,
+  MSG_VIEW_AREA = 15,
+  MSG_PICKUP_SPAWNED = 16,
+  MSG_PICKUP_DESPAWNED = 17,
+  MSG_COLLECT_PICKUP = 18,
+  MSG_PURCHASE_UPGRADE = 19,
+  MSG_UPGRADE_PURCHASED = 20,
+  CAPABILITY_BATCH = 1,
+  CAPABILITY_COMPACT_STATE = 2,
+  maxBatchSize = 256,
+  compactCellSize = 1024,
+  compactPositionSteps = 64,
+  compactSpeedSteps = 16,
+  compactTimeSteps = 10,
+  COMPACT_TIME = 1,
+  COMPACT_CELL = 2,
+  COMPACT_OFFSET = 4,
+  COMPACT_VELOCITY = 8,
+  COMPACT_FLAGS = 16,
+  // player state and input flags.
+  FLAG_DASH = 1,
+  // upgrade kinds (index of `UpgradeKind` variant in client).
+  UPGRADE_SPEED = 0,
+  UPGRADE_PICKUP_RADIUS = 1,
+  UPGRADE_DECAY_RATE = 2;
+
+class ID {
+  constructor(id) {
+    this.id = id;
+  }
+}
+
+function writeHeader(id) {
+  const buffer = Buffer.alloc(8);
+  buffer.writeUInt32BE(id, 0);
+  buffer.writeUInt32BE(version, 4);
+  return buffer;
+}
+
+function writeID(value) {
+  const buffer = Buffer.alloc(4);
+  buffer.writeUInt32BE(value.id, 0);
+  return buffer;
+}
+
+function writeNumber(value) {
+  const buffer = Buffer.alloc(4);
+  buffer.writeFloatBE(value, 0);
+  return buffer;
+}
+
+function writeDouble(value) {
+  const buffer = Buffer.alloc(8);
+  buffer.writeDoubleBE(value, 0);
+  return buffer;
+}
+
+function writeInteger(value) {
+  const buffer = Buffer.alloc(4);
+  buffer.writeUInt32BE(value, 0);
+  return buffer;
+}
+
+function writeByte(value) {
+  return Buffer.from([value]);
+}
+
+function writeShort(value) {
+  const buffer = Buffer.alloc(2);
+  buffer.writeUInt16BE(value, 0);
+  return buffer;
+}
+
+function writeString(value) {
+  const buffer = Buffer.from(value);
+  return Buffer.concat([writeInteger(buffer.length), buffer]);
+}
+
+function writeMessage(id, data) {
+  const parts = [writeHeader(id)];
+  for (var i = 0; i < data.length; ++i) {
+    const item = data[i];
+    if (item instanceof Buffer) {
+      parts.push(item);
+    } else if (item instanceof ID) {
+      parts.push(writeID(item));
+    } else if (typeof item === 'number') {
+      parts.push(writeNumber(item));
+    } else if (typeof item === 'string') {
+      parts.push(writeString(item));
+    }
+  }
+  return Buffer.concat(parts);
+}
+
+// Packs already written frames into one batch frame (each prefixed with its size).
+function writeBatch(frames) {
+  const parts = [writeHeader(MSG_BATCH), writeInteger(frames.length)];
+  for (var i = 0; i < frames.length; ++i) {
+    parts.push(writeInteger(frames[i].length), frames[i]);
+  }
+  return Buffer.concat(parts);
+}
+
+// Reads compact player state (see `MsgPlayerStateCompact` in client), fields that did not
+// change since baseline are null.
+function readCompactState(msg) {
+  const state = {
+    id: msg.readUInt32BE(8),
+    sequence: msg.readUInt16BE(12),
+    baseline: msg.readUInt16BE(14),
+    timestamp: msg.readUInt32BE(16),
+    time: null,
+    cell: null,
+    offset: null,
+    velocity: null,
+    flags: null,
+  };
+  const fields = msg.readUInt8(20);
+  var offset = 21;
+  if (fields & COMPACT_TIME) {
+    state.time = msg.readUInt16BE(offset);
+    offset += 2;
+  }
+  if (fields & COMPACT_CELL) {
+    state.cell = [msg.readInt16BE(offset), msg.readInt16BE(offset + 2)];
+    offset += 4;
+  }
+  if (fields & COMPACT_OFFSET) {
+    state.offset = [msg.readUInt16BE(offset), msg.readUInt16BE(offset + 2)];
+    offset += 4;
+  }
+  if (fields & COMPACT_VELOCITY) {
+    state.velocity = [msg.readUInt8(offset), msg.readUInt16BE(offset + 1)];
+    offset += 3;
+  }
+  if (fields & COMPACT_FLAGS) {
+    state.flags = msg.readUInt8(offset);
+  }
+  return state;
+}
+
+// Fills fields missing in delta state from absolute baseline, returns null without baseline.
+function resolveCompactState(state, baseline) {
+  if (state.baseline === 0) {
+    return state;
+  }
+  if (!baseline || baseline.sequence !== state.baseline) {
+    return null;
+  }
+  return {
+    id: state.id,
+    sequence: state.sequence,
+    baseline: 0,
+    timestamp: state.timestamp,
+    time: state.time !== null ? state.time : baseline.time,
+    cell: state.cell !== null ? state.cell : baseline.cell,
+    offset: state.offset !== null ? state.offset : baseline.offset,
+    velocity: state.velocity !== null ? state.velocity : baseline.velocity,
+    flags: state.flags !== null ? state.flags : baseline.flags,
+  };
+}
+
+function writeCompactState(state) {
+  var fields = 0;
+  const parts = [
+    writeHeader(MSG_PLAYER_STATE_COMPACT),
+    writeInteger(state.id),
+    writeShort(state.sequence),
+    writeShort(state.baseline),
+    writeInteger(state.timestamp),
+    null,
+  ];
+  if (state.time !== null) {
+    fields |= COMPACT_TIME;
+    parts.push(writeShort(state.time));
+  }
+  if (state.cell !== null) {
+    fields |= COMPACT_CELL;
+    const buffer = Buffer.alloc(4);
+    buffer.writeInt16BE(state.cell[0], 0);
+    buffer.writeInt16BE(state.cell[1], 2);
+    parts.push(buffer);
+  }
+  if (state.offset !== null) {
+    fields |= COMPACT_OFFSET;
+    parts.push(writeShort(state.offset[0]), writeShort(state.offset[1]));
+  }
+  if (state.velocity !== null) {
+    fields |= COMPACT_VELOCITY;
+    parts.push(writeByte(state.velocity[0]), writeShort(state.velocity[1]));
+  }
+  if (state.flags !== null) {
+    fields |= COMPACT_FLAGS;
+    parts.push(writeByte(state.flags));
+  }
+  parts[5] = writeByte(fields);
+  return Buffer.concat(parts);
+}
+
+// Converts absolute compact state into plain numbers.
+function compactToState(state) {
+  const cell = state.cell || [0, 0];
+  const offset = state.offset || [0, 0];
+  const velocity = state.velocity || [0, 0];
+  const angle = velocity[0] / 256 * Math.PI * 2;
+  const speed = velocity[1] / compactSpeedSteps;
+  return {
+    id: state.id,
+    time: (state.time || 0) / compactTimeSteps,
+    x: cell[0] * compactCellSize + offset[0] / compactPositionSteps,
+    y: cell[1] * compactCellSize + offset[1] / compactPositionSteps,
+    vx: speed > 0 ? Math.cos(angle) * speed : 0,
+    vy: speed > 0 ? Math.sin(angle) * speed : 0,
+    timestamp: state.timestamp / 1000,
+    flags: state.flags || 0,
+  };
+}
+
+module.exports = {
+  ID,
+  version,
+  MSG_INIT_PLAYER,
+  MSG_NEW_PLAYER,
+  MSG_PLAYER_STATE,
+  MSG_PLAYER_DISCONNECTED,
+  MSG_PLAYER_EAT,
+  MSG_HELLO,
+  MSG_WELCOME,
+  MSG_PLAYER_INPUT,
+  MSG_PLAYER_CORRECTION,
+  MSG_PING,
+  MSG_PONG,
+  MSG_BATCH,
+  MSG_PLAYER_STATE_COMPACT,
+  MSG_PLAYER_STATE_ACK,
+  MSG_VIEW_AREA,
+  MSG_PICKUP_SPAWNED,
+  MSG_PICKUP_DESPAWNED,
+  MSG_COLLECT_PICKUP,
+  MSG_PURCHASE_UPGRADE,
+  MSG_UPGRADE_PURCHASED,
+  CAPABILITY_BATCH,
+  CAPABILITY_COMPACT_STATE,
+  FLAG_DASH,
+  UPGRADE_SPEED,
+  UPGRADE_PICKUP_RADIUS,
+  UPGRADE_DECAY_RATE,
+  maxBatchSize,
+  writeHeader,
+  writeID,
+  writeNumber,
+  writeDouble,
+  writeInteger,
+  writeByte,
+  writeShort,
+  writeString,
+  writeMessage,
+  writeBatch,
+  readCompactState,
+  resolveCompactState,
+  writeCompactState,
+  compactToState,
+};